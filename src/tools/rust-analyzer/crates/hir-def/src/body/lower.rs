@@ -19,7 +19,7 @@
         self, ArrayExprKind, AstChildren, HasArgList, HasLoopBody, HasName, LiteralKind,
         SlicePatComponents,
     },
-    AstNode, AstPtr, SyntaxNodePtr,
+    AstNode, AstPtr, SyntaxNodePtr, T,
 };
 
 use crate::{
@@ -913,16 +913,10 @@ fn collect_pat_(&mut self, pat: ast::Pat) -> PatId {
                     suffix: suffix.into_iter().map(|p| self.collect_pat_(p)).collect(),
                 }
             }
-            ast::Pat::LiteralPat(lit) => {
-                if let Some(ast_lit) = lit.literal() {
-                    let expr = Expr::Literal(ast_lit.kind().into());
-                    let expr_ptr = AstPtr::new(&ast::Expr::Literal(ast_lit));
-                    let expr_id = self.alloc_expr(expr, expr_ptr);
-                    Pat::Lit(expr_id)
-                } else {
-                    Pat::Missing
-                }
-            }
+            ast::Pat::LiteralPat(lit) => match self.collect_lit_pat(&lit) {
+                Some(expr_id) => Pat::Lit(expr_id),
+                None => Pat::Missing,
+            },
             ast::Pat::RestPat(_) => {
                 // `RestPat` requires special handling and should not be mapped
                 // to a Pat. Here we are using `Pat::Missing` as a fallback for
@@ -957,8 +951,23 @@ fn collect_pat_(&mut self, pat: ast::Pat) -> PatId {
                 }
                 None => Pat::Missing,
             },
-            // FIXME: implement
-            ast::Pat::RangePat(_) => Pat::Missing,
+            ast::Pat::RangePat(p) => {
+                let is_inclusive = p
+                    .syntax()
+                    .children_with_tokens()
+                    .any(|it| matches!(it.kind(), T![..=] | T![...]));
+                let range_type =
+                    if is_inclusive { ast::RangeOp::Inclusive } else { ast::RangeOp::Exclusive };
+                match (p.start(), p.end()) {
+                    // FIXME: implement half-open ranges
+                    (Some(start), Some(end)) => {
+                        let start = self.collect_range_pat_bound(start);
+                        let end = self.collect_range_pat_bound(end);
+                        Pat::Range { start, end, range_type }
+                    }
+                    _ => Pat::Missing,
+                }
+            }
         };
         let ptr = AstPtr::new(&pat);
         self.alloc_pat(pattern, Either::Left(ptr))
@@ -971,6 +980,38 @@ fn collect_pat_opt_(&mut self, pat: Option<ast::Pat>) -> PatId {
         }
     }
 
+    /// Lowers the literal of a literal pattern, taking its minus sign into account.
+    fn collect_lit_pat(&mut self, lit: &ast::LiteralPat) -> Option<ExprId> {
+        let ast_lit = lit.literal()?;
+        let mut hir_lit: Literal = ast_lit.kind().into();
+        if lit.syntax().first_token().map_or(false, |it| it.kind() == T![-]) {
+            hir_lit = hir_lit.negate()?;
+        }
+        let expr_ptr = AstPtr::new(&ast::Expr::Literal(ast_lit));
+        Some(self.alloc_expr(Expr::Literal(hir_lit), expr_ptr))
+    }
+
+    /// Lowers an endpoint of a range pattern into the expression it denotes.
+    fn collect_range_pat_bound(&mut self, pat: ast::Pat) -> ExprId {
+        match pat {
+            ast::Pat::LiteralPat(lit) => match self.collect_lit_pat(&lit) {
+                Some(expr_id) => expr_id,
+                None => self.missing_expr(),
+            },
+            ast::Pat::PathPat(p) => {
+                match p.path().and_then(|path| self.expander.parse_path(self.db, path)) {
+                    Some(path) => self.alloc_expr_desugared(Expr::Path(path)),
+                    None => self.missing_expr(),
+                }
+            }
+            ast::Pat::IdentPat(p) if p.pat().is_none() => match p.name() {
+                Some(name) => self.alloc_expr_desugared(Expr::Path(name.as_name().into())),
+                None => self.missing_expr(),
+            },
+            _ => self.missing_expr(),
+        }
+    }
+
     fn collect_tuple_pat(&mut self, args: AstChildren<ast::Pat>) -> (Box<[PatId]>, Option<usize>) {
         // Find the location of the `..`, if there is one. Note that we do not
         // consider the possibility of there being multiple `..` here.
@@ -486,9 +486,12 @@ fn print_pat(&mut self, pat: PatId) {
                 });
                 w!(self, "}}");
             }
-            Pat::Range { start, end } => {
+            Pat::Range { start, end, range_type } => {
                 self.print_expr(*start);
-                w!(self, "...");
+                match range_type {
+                    ast::RangeOp::Exclusive => w!(self, ".."),
+                    ast::RangeOp::Inclusive => w!(self, "..="),
+                }
                 self.print_expr(*end);
             }
             Pat::Slice { prefix, slice, suffix } => {
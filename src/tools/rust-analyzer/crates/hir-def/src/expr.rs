@@ -45,7 +45,7 @@ pub struct Label {
 // We convert float values into bits and that's how we don't need to deal with f32 and f64.
 // For PartialEq, bits comparison should work, as ordering is not important
 // https://github.com/rust-lang/rust-analyzer/issues/12380#issuecomment-1137284360
-#[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[derive(Default, Debug, Clone, Eq, PartialEq, Hash)]
 pub struct FloatTypeWrapper(u64);
 
 impl FloatTypeWrapper {
@@ -60,7 +60,7 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub enum Literal {
     String(Box<str>),
     ByteString(Box<[u8]>),
@@ -74,6 +74,29 @@ pub enum Literal {
     Float(FloatTypeWrapper, Option<BuiltinFloat>),
 }
 
+impl Literal {
+    /// Returns the literal preceded by a minus sign, as in the pattern `-1`. Returns `None` if the
+    /// literal can't be negated.
+    pub fn negate(self) -> Option<Self> {
+        match self {
+            Literal::Int(it, ty) => Some(Literal::Int(it.checked_neg()?, ty)),
+            Literal::Uint(it, None) => {
+                let it = match i128::try_from(it) {
+                    Ok(it) => -it,
+                    // `-170141183460469231731687303715884105728` is `i128::MIN`.
+                    Err(_) if it == i128::MIN.unsigned_abs() => i128::MIN,
+                    Err(_) => return None,
+                };
+                Some(Literal::Int(it, None))
+            }
+            Literal::Float(FloatTypeWrapper(it), ty) => {
+                Some(Literal::Float(FloatTypeWrapper::new(-f64::from_bits(it)), ty))
+            }
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Expr {
     /// This is produced if the syntax tree does not have a required expression piece.
@@ -415,7 +438,7 @@ pub enum Pat {
     Tuple { args: Box<[PatId]>, ellipsis: Option<usize> },
     Or(Box<[PatId]>),
     Record { path: Option<Box<Path>>, args: Box<[RecordFieldPat]>, ellipsis: bool },
-    Range { start: ExprId, end: ExprId },
+    Range { start: ExprId, end: ExprId, range_type: RangeOp },
     Slice { prefix: Box<[PatId]>, slice: Option<PatId>, suffix: Box<[PatId]> },
     Path(Box<Path>),
     Lit(ExprId),
@@ -7,6 +7,7 @@
 
 use chalk_ir::{BoundVar, DebruijnIndex, GenericArgData, IntTy, Scalar};
 use hir_def::{
+    body::Body,
     builtin_type::BuiltinInt,
     expr::{ArithOp, BinaryOp, Expr, ExprId, Literal, Pat, PatId},
     path::ModPath,
@@ -489,6 +490,28 @@ pub(crate) fn const_eval_variant_query(
     result
 }
 
+/// Evaluates `expr`, an expression of the body of `owner` that doesn't refer to its locals, like
+/// the block of an inline `const { .. }`.
+pub(crate) fn eval_const_in_body(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    body: &Body,
+    infer: &InferenceResult,
+    expr: ExprId,
+) -> Result<ComputedExpr, ConstEvalError> {
+    eval_const(
+        expr,
+        &mut ConstEvalCtx {
+            db,
+            owner,
+            exprs: &body.exprs,
+            pats: &body.pats,
+            local_data: HashMap::default(),
+            infer,
+        },
+    )
+}
+
 pub(crate) fn const_eval_query_variant(
     db: &dyn HirDatabase,
     variant_id: EnumVariantId,
@@ -29,6 +29,13 @@ pub trait HirDatabase: DefDatabase + Upcast<dyn DefDatabase> {
     #[salsa::invoke(crate::infer::infer_query)]
     fn infer_query(&self, def: DefWithBodyId) -> Arc<InferenceResult>;
 
+    #[salsa::invoke(crate::diagnostics::match_check_query)]
+    fn match_check(
+        &self,
+        def: DefWithBodyId,
+        match_expr: ExprId,
+    ) -> Option<Arc<crate::diagnostics::MatchCheckResult>>;
+
     #[salsa::invoke(crate::lower::ty_query)]
     #[salsa::cycle(crate::lower::ty_recover)]
     fn ty(&self, def: TyDefId) -> Binders<Ty>;
@@ -8,6 +8,10 @@
     decl_check::{incorrect_case, IncorrectCase},
     expr::{
         record_literal_missing_fields, record_pattern_missing_fields, BodyValidationDiagnostic,
+        MatchCheckResult,
     },
+    match_check::PatternContext,
     unsafe_check::{missing_unsafe, unsafe_expressions, UnsafeExpr},
 };
+
+pub(crate) use crate::diagnostics::expr::match_check_query;
@@ -2,7 +2,6 @@
 //! through the body using inference results: mismatched arg counts, missing
 //! fields, etc.
 
-use std::fmt;
 use std::sync::Arc;
 
 use hir_def::{path::path, resolver::HasResolver, AdtId, AssocItemId, DefWithBodyId, HasModule};
@@ -17,7 +16,8 @@
     diagnostics::match_check::{
         self,
         deconstruct_pat::DeconstructedPat,
-        usefulness::{compute_match_usefulness, MatchCheckCtx},
+        usefulness::{compute_match_usefulness, DisplayWitness, MatchCheckCtx},
+        PatNameCache, PatternContext, RefutablePattern,
     },
     display::HirDisplay,
     InferenceResult, Ty, TyExt,
@@ -25,7 +25,7 @@
 
 pub(crate) use hir_def::{
     body::Body,
-    expr::{Expr, ExprId, MatchArm, Pat, PatId},
+    expr::{BinaryOp, Expr, ExprId, LogicOp, MatchArm, Pat, PatId, Statement},
     LocalFieldId, VariantId,
 };
 
@@ -42,6 +42,15 @@ pub enum BodyValidationDiagnostic {
         match_expr: ExprId,
         uncovered_patterns: String,
     },
+    RefutablePattern {
+        pat: PatId,
+        context: PatternContext,
+        uncovered_patterns: String,
+    },
+    IrrefutableLetPatterns {
+        /// The condition of an `if let` chain, or the pattern of a `let ... else`.
+        target: Either<ExprId, PatId>,
+    },
 }
 
 impl BodyValidationDiagnostic {
@@ -68,7 +77,12 @@ fn new(owner: DefWithBodyId, infer: Arc<InferenceResult>) -> ExprValidator {
     fn validate_body(&mut self, db: &dyn HirDatabase) {
         let body = db.body(self.owner);
         let mut filter_map_next_checker = None;
+        let pattern_arena = Arena::new();
+        let cx = MatchCheckCtx::new(self.owner.module(db.upcast()), self.owner, db, &pattern_arena);
 
+        for &param in body.params.iter() {
+            self.validate_irrefutable(&cx, param, PatternContext::FnParam);
+        }
         for (id, expr) in body.exprs.iter() {
             if let Some((variant, missed_fields, true)) =
                 record_literal_missing_fields(db, &self.infer, id, expr)
@@ -81,12 +95,56 @@ fn validate_body(&mut self, db: &dyn HirDatabase) {
             }
 
             match expr {
-                Expr::Match { expr, arms } => {
-                    self.validate_match(id, *expr, arms, db, self.infer.clone());
+                Expr::Match { .. } => {
+                    self.validate_match(id, db);
                 }
                 Expr::Call { .. } | Expr::MethodCall { .. } => {
                     self.validate_call(db, id, expr, &mut filter_map_next_checker);
                 }
+                &Expr::For { pat, .. } => {
+                    let refutable = match_check::check_for_loop(&cx, id);
+                    self.report_refutable(&cx, pat, refutable);
+                }
+                Expr::Closure { args, .. } => {
+                    for &arg in args.iter() {
+                        self.validate_irrefutable(&cx, arg, PatternContext::FnParam);
+                    }
+                }
+                &Expr::If { condition, .. } => {
+                    let mut lets = Vec::new();
+                    if let_chain_lets(&body, condition, &mut lets)
+                        && match_check::check_let_chain(&cx, &lets).is_else_unreachable()
+                    {
+                        self.diagnostics.push(BodyValidationDiagnostic::IrrefutableLetPatterns {
+                            target: Either::Left(condition),
+                        });
+                    }
+                }
+                Expr::Block { statements, .. } => {
+                    for stmt in statements.iter() {
+                        match *stmt {
+                            Statement::Let { pat, else_branch: None, .. } => {
+                                self.validate_irrefutable(&cx, pat, PatternContext::LetBinding);
+                            }
+                            Statement::Let {
+                                pat,
+                                initializer: Some(init),
+                                else_branch: Some(_),
+                                ..
+                            } => {
+                                let report = match_check::check_let_chain(&cx, &[(pat, init)]);
+                                if report.is_else_unreachable() {
+                                    self.diagnostics.push(
+                                        BodyValidationDiagnostic::IrrefutableLetPatterns {
+                                            target: Either::Right(pat),
+                                        },
+                                    );
+                                }
+                            }
+                            Statement::Let { .. } | Statement::Expr { .. } => {}
+                        }
+                    }
+                }
                 _ => {}
             }
         }
@@ -143,99 +201,191 @@ fn validate_call(
         };
     }
 
-    fn validate_match(
-        &mut self,
-        id: ExprId,
-        match_expr: ExprId,
-        arms: &[MatchArm],
-        db: &dyn HirDatabase,
-        infer: Arc<InferenceResult>,
-    ) {
-        let body = db.body(self.owner);
-
-        let match_expr_ty = &infer[match_expr];
-        if match_expr_ty.is_unknown() {
-            return;
-        }
-
-        let pattern_arena = Arena::new();
-        let cx = MatchCheckCtx::new(self.owner.module(db.upcast()), self.owner, db, &pattern_arena);
-
-        let mut m_arms = Vec::with_capacity(arms.len());
-        let mut has_lowering_errors = false;
-        for arm in arms {
-            if let Some(pat_ty) = infer.type_of_pat.get(arm.pat) {
-                // We only include patterns whose type matches the type
-                // of the match expression. If we had an InvalidMatchArmPattern
-                // diagnostic or similar we could raise that in an else
-                // block here.
-                //
-                // When comparing the types, we also have to consider that rustc
-                // will automatically de-reference the match expression type if
-                // necessary.
-                //
-                // FIXME we should use the type checker for this.
-                if (pat_ty == match_expr_ty
-                    || match_expr_ty
-                        .as_reference()
-                        .map(|(match_expr_ty, ..)| match_expr_ty == pat_ty)
-                        .unwrap_or(false))
-                    && types_of_subpatterns_do_match(arm.pat, &body, &infer)
-                {
-                    // If we had a NotUsefulMatchArm diagnostic, we could
-                    // check the usefulness of each pattern as we added it
-                    // to the matrix here.
-                    let m_arm = match_check::MatchArm {
-                        pat: self.lower_pattern(&cx, arm.pat, db, &body, &mut has_lowering_errors),
-                        has_guard: arm.guard.is_some(),
-                    };
-                    m_arms.push(m_arm);
-                    if !has_lowering_errors {
-                        continue;
-                    }
-                }
-            }
-
-            // If we can't resolve the type of a pattern, or the pattern type doesn't
-            // fit the match expression, we skip this diagnostic. Skipping the entire
-            // diagnostic rather than just not including this match arm is preferred
-            // to avoid the chance of false positives.
-            cov_mark::hit!(validate_match_bailed_out);
-            return;
-        }
-
-        let report = compute_match_usefulness(&cx, &m_arms, match_expr_ty);
+    fn validate_match(&mut self, id: ExprId, db: &dyn HirDatabase) {
+        let result = match db.match_check(self.owner, id) {
+            Some(it) => it,
+            None => return,
+        };
 
         // FIXME Report unreacheble arms
         // https://github.com/rust-lang/rust/blob/f31622a50/compiler/rustc_mir_build/src/thir/pattern/check_match.rs#L200
 
-        let witnesses = report.non_exhaustiveness_witnesses;
-        if !witnesses.is_empty() {
+        if let Some(uncovered_patterns) = &result.uncovered_patterns {
             self.diagnostics.push(BodyValidationDiagnostic::MissingMatchArms {
                 match_expr: id,
-                uncovered_patterns: missing_match_arms(&cx, match_expr_ty, witnesses, arms),
+                uncovered_patterns: uncovered_patterns.clone(),
             });
         }
     }
 
-    fn lower_pattern<'p>(
-        &self,
+    fn validate_irrefutable(
+        &mut self,
+        cx: &MatchCheckCtx<'_, '_>,
+        pat: PatId,
+        context: PatternContext,
+    ) {
+        let refutable = match_check::check_irrefutable(cx, pat, &self.infer[pat], context);
+        self.report_refutable(cx, pat, refutable);
+    }
+
+    fn report_refutable<'p>(
+        &mut self,
         cx: &MatchCheckCtx<'_, 'p>,
         pat: PatId,
-        db: &dyn HirDatabase,
-        body: &Body,
-        have_errors: &mut bool,
-    ) -> &'p DeconstructedPat<'p> {
-        let mut patcx = match_check::PatCtxt::new(db, &self.infer, body);
-        let pattern = patcx.lower_pattern(pat);
-        let pattern = cx.pattern_arena.alloc(DeconstructedPat::from_pat(cx, &pattern));
-        if !patcx.errors.is_empty() {
-            *have_errors = true;
+        refutable: Option<RefutablePattern<'p>>,
+    ) {
+        if let Some(refutable) = refutable {
+            self.diagnostics.push(BodyValidationDiagnostic::RefutablePattern {
+                pat,
+                context: refutable.context,
+                uncovered_patterns: uncovered_patterns(cx, &refutable.witnesses),
+            });
         }
-        pattern
     }
 }
 
+/// Collects the patterns of the `let`s of `condition`, with the expressions they match, into
+/// `lets`. Returns whether `condition` is a chain of `let`s joined by `&&`, without any other
+/// condition.
+fn let_chain_lets(body: &Body, condition: ExprId, lets: &mut Vec<(PatId, ExprId)>) -> bool {
+    match body[condition] {
+        Expr::Let { pat, expr } => {
+            lets.push((pat, expr));
+            true
+        }
+        Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::LogicOp(LogicOp::And)) } => {
+            let_chain_lets(body, lhs, lets) && let_chain_lets(body, rhs, lets)
+        }
+        _ => false,
+    }
+}
+
+/// The outcome of checking a single `match` expression.
+///
+/// This is the output of the `match_check` query, so it must not refer to the
+/// pattern arena the check ran in: arms are identified by their index in the
+/// match expression, which stays stable as long as the body does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchCheckResult {
+    /// Whether each arm of the match is reachable, in source order.
+    pub arm_reachable: Vec<bool>,
+    /// The rendered list of uncovered patterns, if the match is not exhaustive.
+    pub uncovered_patterns: Option<String>,
+}
+
+/// Checks the `match` expression `id` of `owner`.
+///
+/// This reads the whole body and inference result of `owner`, so the result is
+/// only reused across edits to other functions: any edit that changes the body
+/// of `owner` checks all of its matches again.
+pub(crate) fn match_check_query(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    id: ExprId,
+) -> Option<Arc<MatchCheckResult>> {
+    let _p = profile::span("match_check_query");
+    let body = db.body(owner);
+    let infer = db.infer(owner);
+    let (match_expr, arms) = match &body[id] {
+        Expr::Match { expr, arms } => (*expr, arms),
+        _ => return None,
+    };
+
+    let match_expr_ty = &infer[match_expr];
+    if match_expr_ty.is_unknown() {
+        return None;
+    }
+
+    let pattern_arena = Arena::new();
+    let cx = MatchCheckCtx::new(owner.module(db.upcast()), owner, db, &pattern_arena);
+
+    let mut m_arms = Vec::with_capacity(arms.len());
+    let mut has_lowering_errors = false;
+    for arm in arms.iter() {
+        if let Some(pat_ty) = infer.type_of_pat.get(arm.pat) {
+            // We only include patterns whose type matches the type
+            // of the match expression. If we had an InvalidMatchArmPattern
+            // diagnostic or similar we could raise that in an else
+            // block here.
+            //
+            // When comparing the types, we also have to consider that rustc
+            // will automatically de-reference the match expression type if
+            // necessary.
+            //
+            // FIXME we should use the type checker for this.
+            if (pat_ty == match_expr_ty
+                || match_expr_ty
+                    .as_reference()
+                    .map(|(match_expr_ty, ..)| match_expr_ty == pat_ty)
+                    .unwrap_or(false))
+                && types_of_subpatterns_do_match(arm.pat, &body, &infer)
+            {
+                // If we had a NotUsefulMatchArm diagnostic, we could
+                // check the usefulness of each pattern as we added it
+                // to the matrix here.
+                let m_arm = match_check::MatchArm {
+                    pat: lower_pattern(
+                        &cx,
+                        owner,
+                        &infer,
+                        arm.pat,
+                        db,
+                        &body,
+                        &mut has_lowering_errors,
+                    ),
+                    has_guard: arm.guard.is_some(),
+                };
+                m_arms.push(m_arm);
+                if !has_lowering_errors {
+                    continue;
+                }
+            }
+        }
+
+        // If we can't resolve the type of a pattern, or the pattern type doesn't
+        // fit the match expression, we skip this diagnostic. Skipping the entire
+        // diagnostic rather than just not including this match arm is preferred
+        // to avoid the chance of false positives.
+        cov_mark::hit!(validate_match_bailed_out);
+        return None;
+    }
+
+    let report = compute_match_usefulness(&cx, &m_arms, match_expr_ty);
+
+    let arm_reachable = report
+        ._arm_usefulness
+        .iter()
+        .map(|(_, reachability)| reachability.is_reachable())
+        .collect();
+    let witnesses = report.non_exhaustiveness_witnesses;
+    // Past the depth limit, the witnesses may miss some of the uncovered values, so we'd rather
+    // not report the match at all.
+    let uncovered_patterns = if witnesses.is_empty() || report.reached_max_depth {
+        None
+    } else {
+        Some(missing_match_arms(&cx, match_expr_ty, witnesses, arms))
+    };
+    Some(Arc::new(MatchCheckResult { arm_reachable, uncovered_patterns }))
+}
+
+fn lower_pattern<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    owner: DefWithBodyId,
+    infer: &InferenceResult,
+    pat: PatId,
+    db: &dyn HirDatabase,
+    body: &Body,
+    have_errors: &mut bool,
+) -> &'p DeconstructedPat<'p> {
+    let mut patcx = match_check::PatCtxt::new(db, owner, infer, body);
+    let pattern = patcx.lower_pattern(pat);
+    let pattern = cx.pattern_arena.alloc(DeconstructedPat::from_pat(cx, &pattern));
+    if !patcx.errors.is_empty() {
+        *have_errors = true;
+    }
+    pattern
+}
+
 struct FilterMapNextChecker {
     filter_map_function_id: Option<hir_def::FunctionId>,
     next_function_id: Option<hir_def::FunctionId>,
@@ -356,7 +506,11 @@ pub fn record_pattern_missing_fields(
     Some((variant_def, missed_fields, exhaustive))
 }
 
-fn types_of_subpatterns_do_match(pat: PatId, body: &Body, infer: &InferenceResult) -> bool {
+pub(super) fn types_of_subpatterns_do_match(
+    pat: PatId,
+    body: &Body,
+    infer: &InferenceResult,
+) -> bool {
     fn walk(pat: PatId, body: &Body, infer: &InferenceResult, has_type_mismatches: &mut bool) {
         match infer.type_mismatch_for_pat(pat) {
             Some(_) => *has_type_mismatches = true,
@@ -377,15 +531,6 @@ fn missing_match_arms<'p>(
     witnesses: Vec<DeconstructedPat<'p>>,
     arms: &[MatchArm],
 ) -> String {
-    struct DisplayWitness<'a, 'p>(&'a DeconstructedPat<'p>, &'a MatchCheckCtx<'a, 'p>);
-    impl<'a, 'p> fmt::Display for DisplayWitness<'a, 'p> {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            let DisplayWitness(witness, cx) = *self;
-            let pat = witness.to_pat(cx);
-            write!(f, "{}", pat.display(cx.db))
-        }
-    }
-
     let non_empty_enum = match scrut_ty.as_adt() {
         Some((AdtId::EnumId(e), _)) => !cx.db.enum_data(e).variants.is_empty(),
         _ => false,
@@ -393,19 +538,31 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     if arms.is_empty() && !non_empty_enum {
         format!("type `{}` is non-empty", scrut_ty.display(cx.db))
     } else {
-        let pat_display = |witness| DisplayWitness(witness, cx);
-        const LIMIT: usize = 3;
-        match &*witnesses {
-            [witness] => format!("`{}` not covered", pat_display(witness)),
-            [head @ .., tail] if head.len() < LIMIT => {
-                let head = head.iter().map(pat_display);
-                format!("`{}` and `{}` not covered", head.format("`, `"), pat_display(tail))
-            }
-            _ => {
-                let (head, tail) = witnesses.split_at(LIMIT);
-                let head = head.iter().map(pat_display);
-                format!("`{}` and {} more not covered", head.format("`, `"), tail.len())
-            }
+        uncovered_patterns(cx, &witnesses)
+    }
+}
+
+/// Renders the first few of `witnesses`, like "`A` and `B` not covered". If the check stopped
+/// looking for witnesses at `max_witnesses`, the others are counted as "at least N more".
+pub(super) fn uncovered_patterns<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    witnesses: &[DeconstructedPat<'p>],
+) -> String {
+    let names = PatNameCache::default();
+    let pat_display = |witness| DisplayWitness(witness, cx, &names);
+    const LIMIT: usize = 3;
+    match witnesses {
+        [witness] => format!("`{}` not covered", pat_display(witness)),
+        [head @ .., tail] if head.len() < LIMIT => {
+            let head = head.iter().map(pat_display);
+            format!("`{}` and `{}` not covered", head.format("`, `"), pat_display(tail))
+        }
+        _ => {
+            let (head, tail) = witnesses.split_at(LIMIT);
+            let head = head.iter().map(pat_display);
+            let capped = cx.config.max_witnesses.map_or(false, |max| witnesses.len() >= max);
+            let at_least = if capped { "at least " } else { "" };
+            format!("`{}` and {}{} more not covered", head.format("`, `"), at_least, tail.len())
         }
     }
 }
@@ -10,22 +10,39 @@
 pub(crate) mod deconstruct_pat;
 pub(crate) mod usefulness;
 
-use chalk_ir::Mutability;
+use std::{cell::RefCell, sync::Arc};
+
+use chalk_ir::{Mutability, TypeFlags};
 use hir_def::{
-    adt::VariantData, body::Body, expr::PatId, AdtId, EnumVariantId, HasModule, LocalFieldId,
-    VariantId,
+    adt::VariantData,
+    body::Body,
+    expr::{Expr, ExprId, Literal, PatId, RangeOp, UnaryOp},
+    path::Path,
+    resolver::{HasResolver, ValueNs},
+    AdtId, ConstId, DefWithBodyId, EnumVariantId, HasModule, LocalFieldId, VariantId,
 };
 use hir_expand::name::{name, Name};
+use rustc_hash::FxHashMap;
 use stdx::{always, never};
 
 use crate::{
+    consteval::{eval_const_in_body, ComputedExpr},
     db::HirDatabase,
     display::{HirDisplay, HirDisplayError, HirFormatter},
-    infer::BindingMode,
+    infer::{normalize, BindingMode},
     InferenceResult, Interner, Substitution, Ty, TyExt, TyKind,
 };
 
-use self::pat_util::EnumerateAndAdjustIterator;
+use self::{
+    deconstruct_pat::{DeconstructedPat, IntRange},
+    pat_util::EnumerateAndAdjustIterator,
+    usefulness::{compute_match_usefulness, MatchCheckCtx, UsefulnessReport},
+};
+
+use super::expr::types_of_subpatterns_do_match;
+
+#[cfg(test)]
+use self::usefulness::Reachability;
 
 pub(crate) use self::usefulness::MatchArm;
 
@@ -38,20 +55,20 @@ pub(crate) enum PatternError {
     ExtraFields,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub(crate) struct FieldPat {
     pub(crate) field: LocalFieldId,
     pub(crate) pattern: Pat,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub(crate) struct Pat {
     pub(crate) ty: Ty,
     pub(crate) kind: Box<PatKind>,
 }
 
 /// Close relative to `rustc_mir_build::thir::pattern::PatKind`
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub(crate) enum PatKind {
     Wild,
 
@@ -85,49 +102,295 @@ pub(crate) enum PatKind {
         value: bool,
     },
 
+    /// A literal, or a `const` evaluated to a literal.
+    // FIXME: for now, only char and integer literals are implemented
+    Lit {
+        value: Literal,
+    },
+
+    /// `lo..=hi`, where `lo` and `hi` are literals or `const`s evaluated to literals.
+    /// Invariant: `lo <= hi`.
+    Range {
+        lo: Literal,
+        hi: Literal,
+    },
+
+    /// Matches against a slice or an array, checking the length and extracting elements.
+    /// Irrefutable when there is a slice pattern and both `prefix` and `suffix` are empty.
+    /// e.g., `&[ref xs @ ..]`.
+    Slice {
+        prefix: Vec<Pat>,
+        slice: Option<Pat>,
+        suffix: Vec<Pat>,
+    },
+
+    /// A `const` that we could not evaluate. It is treated as a black box for the purposes of
+    /// exhaustiveness: it never counts towards making a match exhaustive.
+    Opaque,
+
     /// An or-pattern, e.g. `p | q`.
-    /// Invariant: `pats.len() >= 2`.
+    /// Invariant: `pats.len() >= 2`, except for patterns that match nothing, like the empty range
+    /// `5..5`, which are lowered to an or-pattern without alternatives.
     Or {
         pats: Vec<Pat>,
     },
 }
 
+/// The result of [`check_let_chain`].
+#[derive(Debug)]
+pub(crate) struct LetChainReport {
+    /// For each `let` of the chain, in order, whether its pattern is irrefutable.
+    pub(crate) irrefutable: Vec<bool>,
+}
+
+impl LetChainReport {
+    /// Whether every `let` of the chain is irrefutable, which makes the `else` branch unreachable.
+    pub(crate) fn is_else_unreachable(&self) -> bool {
+        self.irrefutable.iter().all(|&it| it)
+    }
+}
+
+/// Checks the refutability of each `let` of an `if let` chain or a `let ... else`, in order. Each
+/// `let` is given as its pattern and the expression the pattern is matched against.
+///
+/// A pattern that fails to lower, doesn't have the type of its expression, or is too deep to check
+/// is conservatively reported as refutable.
+pub(crate) fn check_let_chain(
+    cx: &MatchCheckCtx<'_, '_>,
+    lets: &[(PatId, ExprId)],
+) -> LetChainReport {
+    let body = cx.db.body(cx.body);
+    let infer = cx.db.infer(cx.body);
+    let irrefutable = lets
+        .iter()
+        .map(|&(pat, expr)| {
+            let mut arms = std::iter::once((pat, false));
+            check_arms(cx, &body, &infer, &infer[expr], &mut arms).map_or(false, |report| {
+                !report.reached_max_depth && report.non_exhaustiveness_witnesses.is_empty()
+            })
+        })
+        .collect();
+    LetChainReport { irrefutable }
+}
+
+/// The places where a pattern has to be irrefutable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PatternContext {
+    /// The pattern of a `let` statement without an `else` branch.
+    LetBinding,
+    /// A parameter of a function or closure.
+    FnParam,
+    /// The pattern of a `for` loop, which each item of the iterator is bound to.
+    ForLoop,
+}
+
+impl PatternContext {
+    /// The wording of the error reported for a refutable pattern in this context.
+    pub fn refutable_message(self) -> &'static str {
+        match self {
+            PatternContext::LetBinding => "refutable pattern in local binding",
+            PatternContext::FnParam => "refutable pattern in function parameter",
+            PatternContext::ForLoop => "refutable pattern in `for` loop binding",
+        }
+    }
+}
+
+/// A pattern that has to be irrefutable but isn't, found by [`check_irrefutable`].
+pub(crate) struct RefutablePattern<'p> {
+    pub(crate) context: PatternContext,
+    /// The values the pattern doesn't match.
+    pub(crate) witnesses: Vec<DeconstructedPat<'p>>,
+}
+
+/// Checks that `pat`, matched against a value of type `ty` in `context`, is irrefutable. Returns
+/// `None` if it is, or if it fails to lower or doesn't have type `ty`.
+pub(crate) fn check_irrefutable<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    pat: PatId,
+    ty: &Ty,
+    context: PatternContext,
+) -> Option<RefutablePattern<'p>> {
+    let body = cx.db.body(cx.body);
+    let infer = cx.db.infer(cx.body);
+    let report = check_arms(cx, &body, &infer, ty, &mut std::iter::once((pat, false)))?;
+    if report.non_exhaustiveness_witnesses.is_empty() {
+        return None;
+    }
+    Some(RefutablePattern { context, witnesses: report.non_exhaustiveness_witnesses })
+}
+
+/// Checks that the pattern of the `for` loop `for_expr` is irrefutable for the item type inference
+/// found for the loop. Returns `None` if it is, or if `for_expr` isn't a `for` loop or its item
+/// type is unknown.
+pub(crate) fn check_for_loop<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    for_expr: ExprId,
+) -> Option<RefutablePattern<'p>> {
+    let body = cx.db.body(cx.body);
+    let infer = cx.db.infer(cx.body);
+    let pat = match body[for_expr] {
+        Expr::For { pat, .. } => pat,
+        _ => return None,
+    };
+    let item_ty = &infer[pat];
+    if item_ty.is_unknown() {
+        return None;
+    }
+    check_irrefutable(cx, pat, item_ty, PatternContext::ForLoop)
+}
+
+/// What a `matches!(scrutinee, pat)` evaluates to, as far as its pattern tells.
+#[cfg(test)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MatchesOutcome {
+    /// The pattern is irrefutable and there is no guard.
+    AlwaysTrue,
+    /// No value of the scrutinee type matches the pattern, e.g. because the pattern has another
+    /// type.
+    AlwaysFalse,
+    /// It depends on the value of the scrutinee.
+    Depends,
+}
+
+/// Checks the pattern `pat` of a `matches!` against a scrutinee of type `ty`. The macro expands
+/// to a match with `pat` and a catch-all arm, so this is the refutability of `pat`, plus whether
+/// it matches anything at all. Returns `None` if `pat` fails to lower.
+#[cfg(test)]
+pub(crate) fn check_matches_macro(
+    cx: &MatchCheckCtx<'_, '_>,
+    pat: PatId,
+    has_guard: bool,
+    ty: &Ty,
+) -> Option<MatchesOutcome> {
+    let body = cx.db.body(cx.body);
+    let infer = cx.db.infer(cx.body);
+    if ty.is_unknown() {
+        return None;
+    }
+    let pat_ty = infer.type_of_pat.get(pat)?;
+    let ty_matches = pat_ty == ty || ty.as_reference().map_or(false, |(ty, ..)| ty == pat_ty);
+    if !ty_matches || !types_of_subpatterns_do_match(pat, &body, &infer) {
+        return Some(MatchesOutcome::AlwaysFalse);
+    }
+    let report = check_arms(cx, &body, &infer, ty, &mut std::iter::once((pat, has_guard)))?;
+    let outcome = match &report._arm_usefulness[..] {
+        [(_, Reachability::Unreachable)] => MatchesOutcome::AlwaysFalse,
+        _ if !has_guard && report.non_exhaustiveness_witnesses.is_empty() => {
+            MatchesOutcome::AlwaysTrue
+        }
+        _ => MatchesOutcome::Depends,
+    };
+    Some(outcome)
+}
+
+/// Returns whether no value of type `ty` matches both patterns `a` and `b`, like `Some(0)` and
+/// `Some(1)`, so that arms with these patterns could be merged. Patterns that fail to lower or
+/// don't have type `ty` are assumed to overlap.
+#[cfg(test)]
+pub(crate) fn arms_disjoint(cx: &MatchCheckCtx<'_, '_>, a: PatId, b: PatId, ty: &Ty) -> bool {
+    let body = cx.db.body(cx.body);
+    let infer = cx.db.infer(cx.body);
+    let mut patcx = PatCtxt::new(cx.db, cx.body, &infer, &body);
+    let a = patcx.lower_pattern(a);
+    let b = patcx.lower_pattern(b);
+    let ty = normalize_pat_ty(cx.db, cx.body, ty);
+    if !patcx.errors.is_empty() || a.ty != ty || b.ty != ty {
+        return false;
+    }
+    DeconstructedPat::from_pat(cx, &a).is_disjoint_from(cx, &DeconstructedPat::from_pat(cx, &b))
+}
+
+/// Lowers the patterns of `arms` and computes their usefulness report, unless a pattern fails to
+/// lower or doesn't have the type of the scrutinee.
+fn check_arms<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    body: &Body,
+    infer: &InferenceResult,
+    scrut_ty: &Ty,
+    arms: &mut dyn Iterator<Item = (PatId, bool)>,
+) -> Option<UsefulnessReport<'p>> {
+    if scrut_ty.is_unknown() {
+        return None;
+    }
+    let mut patcx = PatCtxt::new(cx.db, cx.body, infer, body);
+    let arms = arms
+        .map(|(pat, has_guard)| {
+            let pat_ty = infer.type_of_pat.get(pat)?;
+            // Like rustc, allow the scrutinee to be auto-dereferenced once.
+            let ty_matches = pat_ty == scrut_ty
+                || scrut_ty.as_reference().map_or(false, |(scrut_ty, ..)| scrut_ty == pat_ty);
+            if !ty_matches || !types_of_subpatterns_do_match(pat, body, infer) {
+                return None;
+            }
+            let pattern = patcx.lower_pattern(pat);
+            let pat = cx.pattern_arena.alloc(DeconstructedPat::from_pat(cx, &pattern));
+            Some(MatchArm { pat, has_guard })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    if !patcx.errors.is_empty() {
+        return None;
+    }
+    Some(compute_match_usefulness(cx, &arms, scrut_ty))
+}
+
+/// Resolves the associated types of `ty` that the bounds in scope of `owner` determine, like
+/// `T::Output` with `T: Trait<Output = Option<i32>>`, so that the constructors of the resolved type
+/// can be listed. If some of them can't be resolved, `ty` is kept as is, and is handled like the
+/// other types we can't see through.
+fn normalize_pat_ty(db: &dyn HirDatabase, owner: DefWithBodyId, ty: &Ty) -> Ty {
+    let has_error = |ty: &Ty| ty.data(Interner).flags.intersects(TypeFlags::HAS_ERROR);
+    let normalized = normalize(db, owner, ty.clone());
+    if has_error(&normalized) && !has_error(ty) {
+        return ty.clone();
+    }
+    normalized
+}
+
 pub(crate) struct PatCtxt<'a> {
     db: &'a dyn HirDatabase,
+    owner: DefWithBodyId,
     infer: &'a InferenceResult,
     body: &'a Body,
     pub(crate) errors: Vec<PatternError>,
 }
 
 impl<'a> PatCtxt<'a> {
-    pub(crate) fn new(db: &'a dyn HirDatabase, infer: &'a InferenceResult, body: &'a Body) -> Self {
-        Self { db, infer, body, errors: Vec::new() }
+    pub(crate) fn new(
+        db: &'a dyn HirDatabase,
+        owner: DefWithBodyId,
+        infer: &'a InferenceResult,
+        body: &'a Body,
+    ) -> Self {
+        Self { db, owner, infer, body, errors: Vec::new() }
     }
 
     pub(crate) fn lower_pattern(&mut self, pat: PatId) -> Pat {
         // XXX(iDawer): Collecting pattern adjustments feels imprecise to me.
-        // When lowering of & and box patterns are implemented this should be tested
-        // in a manner of `match_ergonomics_issue_9095` test.
         // Pattern adjustment is part of RFC 2005-match-ergonomics.
         // More info https://github.com/rust-lang/rust/issues/42640#issuecomment-313535089
         let unadjusted_pat = self.lower_pattern_unadjusted(pat);
         self.infer.pat_adjustments.get(&pat).map(|it| &**it).unwrap_or_default().iter().rev().fold(
             unadjusted_pat,
             |subpattern, ref_ty| Pat {
-                ty: ref_ty.clone(),
+                ty: normalize_pat_ty(self.db, self.owner, ref_ty),
                 kind: Box::new(PatKind::Deref { subpattern }),
             },
         )
     }
 
     fn lower_pattern_unadjusted(&mut self, pat: PatId) -> Pat {
-        let mut ty = &self.infer[pat];
+        let ty = normalize_pat_ty(self.db, self.owner, &self.infer[pat]);
+        let mut ty = &ty;
         let variant = self.infer.variant_resolution_for_pat(pat);
 
         let kind = match self.body[pat] {
             hir_def::expr::Pat::Wild => PatKind::Wild,
 
-            hir_def::expr::Pat::Lit(expr) => self.lower_lit(expr),
+            hir_def::expr::Pat::Lit(expr) => self.lower_lit(ty, expr),
+
+            hir_def::expr::Pat::Range { start, end, range_type } => {
+                self.lower_range(ty, start, end, range_type)
+            }
 
             hir_def::expr::Pat::Path(ref path) => {
                 return self.lower_path(pat, path);
@@ -193,6 +456,43 @@ fn lower_pattern_unadjusted(&mut self, pat: PatId) -> Pat {
 
             hir_def::expr::Pat::Or(ref pats) => PatKind::Or { pats: self.lower_patterns(pats) },
 
+            hir_def::expr::Pat::Ref { pat: subpat, .. } => match ty.kind(Interner) {
+                TyKind::Ref(..) => PatKind::Deref { subpattern: self.lower_pattern(subpat) },
+                _ => {
+                    never!("unexpected type for reference pattern: {:?}", ty);
+                    self.errors.push(PatternError::UnexpectedType);
+                    PatKind::Wild
+                }
+            },
+
+            hir_def::expr::Pat::Box { inner } => match ty.kind(Interner) {
+                TyKind::Adt(adt, _) if is_box(adt.0, self.db) => {
+                    PatKind::Deref { subpattern: self.lower_pattern(inner) }
+                }
+                _ => {
+                    never!("unexpected type for box pattern: {:?}", ty);
+                    self.errors.push(PatternError::UnexpectedType);
+                    PatKind::Wild
+                }
+            },
+
+            hir_def::expr::Pat::Slice { ref prefix, slice, ref suffix } => {
+                match ty.kind(Interner) {
+                    TyKind::Array(..) | TyKind::Slice(..) => PatKind::Slice {
+                        prefix: self.lower_patterns(prefix),
+                        slice: self.lower_opt_pattern(slice),
+                        suffix: self.lower_patterns(suffix),
+                    },
+                    _ => {
+                        never!("unexpected type for slice pattern: {:?}", ty);
+                        self.errors.push(PatternError::UnexpectedType);
+                        PatKind::Wild
+                    }
+                }
+            }
+
+            hir_def::expr::Pat::ConstBlock(expr) => self.lower_const_block(ty, expr),
+
             _ => {
                 self.errors.push(PatternError::Unimplemented);
                 PatKind::Wild
@@ -264,67 +564,293 @@ fn lower_variant_or_leaf(
         kind
     }
 
-    fn lower_path(&mut self, pat: PatId, _path: &hir_def::path::Path) -> Pat {
+    fn lower_path(&mut self, pat: PatId, path: &Path) -> Pat {
         let ty = &self.infer[pat];
 
         let pat_from_kind = |kind| Pat { ty: ty.clone(), kind: Box::new(kind) };
 
         match self.infer.variant_resolution_for_pat(pat) {
             Some(_) => pat_from_kind(self.lower_variant_or_leaf(pat, ty, Vec::new())),
-            None => {
-                self.errors.push(PatternError::UnresolvedVariant);
-                pat_from_kind(PatKind::Wild)
-            }
+            None if IntRange::is_integral(ty) => match self.eval_const_path(path) {
+                Some(value) if IntRange::from_literal(&value, ty).is_some() => {
+                    pat_from_kind(PatKind::Lit { value })
+                }
+                _ => pat_from_kind(PatKind::Opaque),
+            },
+            None => match self.resolve_const(path) {
+                // Constants built from variants, structs, tuples and literals are matched like
+                // the pattern that spells out their value. Other constants are opaque.
+                Some(id) => match self.lower_const(id) {
+                    Some(pat) => Pat { ty: ty.clone(), kind: pat.kind },
+                    None => pat_from_kind(PatKind::Opaque),
+                },
+                None => {
+                    self.errors.push(PatternError::UnresolvedVariant);
+                    pat_from_kind(PatKind::Wild)
+                }
+            },
         }
     }
 
-    fn lower_lit(&mut self, expr: hir_def::expr::ExprId) -> PatKind {
-        use hir_def::expr::{Expr, Literal::Bool};
+    fn lower_lit(&mut self, ty: &Ty, expr: ExprId) -> PatKind {
+        use hir_def::expr::{
+            Expr,
+            Literal::{Bool, ByteString},
+        };
 
         match self.body[expr] {
             Expr::Literal(Bool(value)) => PatKind::LiteralBool { value },
+            // String literals are only lowered for `&str`, the only type they can match stably.
+            Expr::Literal(ref value @ Literal::String(_))
+                if ty
+                    .as_reference()
+                    .map_or(false, |(ty, ..)| matches!(ty.kind(Interner), TyKind::Str)) =>
+            {
+                PatKind::Lit { value: value.clone() }
+            }
+            // FIXME: look inside byte strings once slice and array patterns are implemented. Until
+            // then they are opaque constants: they don't make a match exhaustive, so a catch-all
+            // arm after them is still reachable.
+            Expr::Literal(ByteString(_)) => PatKind::Opaque,
+            Expr::Literal(ref value) if IntRange::from_literal(value, ty).is_some() => {
+                PatKind::Lit { value: value.clone() }
+            }
             _ => {
                 self.errors.push(PatternError::Unimplemented);
                 PatKind::Wild
             }
         }
     }
+
+    /// Evaluates the inline constant `const { .. }` of block `expr` and lowers it like a literal
+    /// of its value. Blocks we can't evaluate are opaque.
+    fn lower_const_block(&self, ty: &Ty, expr: ExprId) -> PatKind {
+        match eval_const_in_body(self.db, self.owner, self.body, self.infer, expr) {
+            Ok(ComputedExpr::Literal(Literal::Bool(value))) => PatKind::LiteralBool { value },
+            Ok(ComputedExpr::Literal(value)) if IntRange::from_literal(&value, ty).is_some() => {
+                PatKind::Lit { value }
+            }
+            _ => PatKind::Opaque,
+        }
+    }
+
+    fn lower_range(&mut self, ty: &Ty, start: ExprId, end: ExprId, range_type: RangeOp) -> PatKind {
+        if !IntRange::is_integral(ty) {
+            self.errors.push(PatternError::Unimplemented);
+            return PatKind::Wild;
+        }
+        let (lo, hi) = match (self.eval_range_bound(start), self.eval_range_bound(end)) {
+            (Some(lo), Some(hi))
+                if IntRange::from_literal(&lo, ty).is_some()
+                    && IntRange::from_literal(&hi, ty).is_some() =>
+            {
+                (lo, hi)
+            }
+            // A bound could not be evaluated, so the pattern must not count towards
+            // exhaustiveness.
+            _ => return PatKind::Opaque,
+        };
+        let hi = match range_type {
+            RangeOp::Inclusive => Some(hi),
+            // `lo..hi` is `lo..=hi - 1`, unless `hi` is the smallest value of the type.
+            RangeOp::Exclusive => IntRange::literal_before(&hi, ty),
+        };
+        match hi {
+            Some(hi) if IntRange::from_literal_range(&lo, &hi, ty).is_some() => {
+                PatKind::Range { lo, hi }
+            }
+            // The range is empty: the pattern matches nothing, which is what an or-pattern without
+            // alternatives does.
+            _ => PatKind::Or { pats: Vec::new() },
+        }
+    }
+
+    fn eval_range_bound(&self, expr: ExprId) -> Option<Literal> {
+        use hir_def::expr::Expr;
+
+        match &self.body[expr] {
+            Expr::Literal(lit) => Some(lit.clone()),
+            Expr::Path(path) => self.eval_const_path(path),
+            _ => None,
+        }
+    }
+
+    /// Evaluates `path` if it resolves to a `const` item with a literal value.
+    fn eval_const_path(&self, path: &Path) -> Option<Literal> {
+        match self.db.const_eval(self.resolve_const(path)?) {
+            Ok(ComputedExpr::Literal(lit)) => Some(lit),
+            _ => None,
+        }
+    }
+
+    fn resolve_const(&self, path: &Path) -> Option<ConstId> {
+        let resolver = self.owner.resolver(self.db.upcast());
+        match resolver.resolve_path_in_value_ns_fully(self.db.upcast(), path.mod_path())? {
+            ValueNs::ConstId(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Lowers the value of the constant `id` to the pattern that matches exactly that value.
+    fn lower_const(&self, id: ConstId) -> Option<Pat> {
+        let owner = DefWithBodyId::from(id);
+        let body = self.db.body(owner);
+        let infer = self.db.infer(owner);
+        const_value_to_pat(self.db, &body, &infer, body.body_expr)
+    }
+}
+
+/// Converts `expr`, a part of the value of a constant, to the pattern that matches exactly its
+/// value. Returns `None` unless `expr` is made only of enum variants, structs, tuples and literals.
+fn const_value_to_pat(
+    db: &dyn HirDatabase,
+    body: &Body,
+    infer: &InferenceResult,
+    expr: ExprId,
+) -> Option<Pat> {
+    let ty = &infer[expr];
+    let to_pat = |expr| const_value_to_pat(db, body, infer, expr);
+    let positional = |exprs: &[ExprId]| {
+        exprs
+            .iter()
+            .enumerate()
+            .map(|(i, &expr)| {
+                let pattern = to_pat(expr)?;
+                Some(FieldPat { field: LocalFieldId::from_raw((i as u32).into()), pattern })
+            })
+            .collect::<Option<Vec<_>>>()
+    };
+    let adt = |variant: VariantId, subpatterns| match variant {
+        VariantId::EnumVariantId(enum_variant) => {
+            let substs = ty.as_adt()?.1.clone();
+            Some(PatKind::Variant { substs, enum_variant, subpatterns })
+        }
+        _ => Some(PatKind::Leaf { subpatterns }),
+    };
+
+    let kind = match &body[expr] {
+        &Expr::Literal(Literal::Bool(value)) => PatKind::LiteralBool { value },
+        Expr::Literal(value) if IntRange::from_literal(value, ty).is_some() => {
+            PatKind::Lit { value: value.clone() }
+        }
+        &Expr::UnaryOp { expr: operand, op: UnaryOp::Neg } => match &body[operand] {
+            Expr::Literal(value) => {
+                let value = value.clone().negate()?;
+                IntRange::from_literal(&value, ty)?;
+                PatKind::Lit { value }
+            }
+            _ => return None,
+        },
+        Expr::Tuple { exprs, .. } => PatKind::Leaf { subpatterns: positional(exprs)? },
+        Expr::Path(_) => {
+            let variant = infer.variant_resolution_for_expr(expr)?;
+            if !variant.variant_data(db.upcast()).fields().is_empty() {
+                return None;
+            }
+            adt(variant, Vec::new())?
+        }
+        Expr::Call { callee, args, .. } => {
+            let variant = infer.variant_resolution_for_expr(*callee)?;
+            adt(variant, positional(args)?)?
+        }
+        Expr::RecordLit { fields, spread: None, .. } => {
+            let variant = infer.variant_resolution_for_expr(expr)?;
+            let variant_data = variant.variant_data(db.upcast());
+            let subpatterns = fields
+                .iter()
+                .map(|field| {
+                    let field_id = variant_data.field(&field.name)?;
+                    Some(FieldPat { field: field_id, pattern: to_pat(field.expr)? })
+                })
+                .collect::<Option<Vec<_>>>()?;
+            adt(variant, subpatterns)?
+        }
+        _ => return None,
+    };
+    Some(Pat { ty: ty.clone(), kind: Box::new(kind) })
+}
+
+/// The names of the variants that displaying patterns refers to, and the data of their fields,
+/// looked up in the database once per variant. Patterns displayed with the same cache share them,
+/// like the witnesses of one match, which are often variants of the same few types.
+#[derive(Default)]
+pub(crate) struct PatNameCache {
+    variants: RefCell<FxHashMap<VariantId, (Name, Arc<VariantData>)>>,
+}
+
+impl PatNameCache {
+    fn variant(&self, db: &dyn HirDatabase, variant: VariantId) -> (Name, Arc<VariantData>) {
+        self.variants
+            .borrow_mut()
+            .entry(variant)
+            .or_insert_with(|| {
+                let name = match variant {
+                    VariantId::EnumVariantId(v) => {
+                        db.enum_data(v.parent).variants[v.local_id].name.clone()
+                    }
+                    VariantId::StructId(s) => db.struct_data(s).name.clone(),
+                    VariantId::UnionId(u) => db.union_data(u).name.clone(),
+                };
+                (name, variant.variant_data(db.upcast()))
+            })
+            .clone()
+    }
+
+    /// The number of variants looked up in the database so far.
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.variants.borrow().len()
+    }
+}
+
+impl Pat {
+    /// Displays `self` like its [`HirDisplay`] implementation does, but looks up the names it
+    /// refers to in `names`.
+    pub(crate) fn display_with_names<'a>(&'a self, names: &'a PatNameCache) -> DisplayPat<'a> {
+        DisplayPat { pat: self, names }
+    }
 }
 
 impl HirDisplay for Pat {
     fn hir_fmt(&self, f: &mut HirFormatter<'_>) -> Result<(), HirDisplayError> {
-        match &*self.kind {
+        self.display_with_names(&PatNameCache::default()).hir_fmt(f)
+    }
+}
+
+/// See [`Pat::display_with_names`].
+pub(crate) struct DisplayPat<'a> {
+    pat: &'a Pat,
+    names: &'a PatNameCache,
+}
+
+impl HirDisplay for DisplayPat<'_> {
+    fn hir_fmt(&self, f: &mut HirFormatter<'_>) -> Result<(), HirDisplayError> {
+        let DisplayPat { pat, names } = *self;
+        match &*pat.kind {
             PatKind::Wild => write!(f, "_"),
             PatKind::Binding { name, subpattern } => {
                 write!(f, "{name}")?;
                 if let Some(subpattern) = subpattern {
                     write!(f, " @ ")?;
-                    subpattern.hir_fmt(f)?;
+                    subpattern.display_with_names(names).hir_fmt(f)?;
                 }
                 Ok(())
             }
             PatKind::Variant { subpatterns, .. } | PatKind::Leaf { subpatterns } => {
-                let variant = match *self.kind {
+                let variant = match *pat.kind {
                     PatKind::Variant { enum_variant, .. } => Some(VariantId::from(enum_variant)),
-                    _ => self.ty.as_adt().and_then(|(adt, _)| match adt {
+                    _ => pat.ty.as_adt().and_then(|(adt, _)| match adt {
                         AdtId::StructId(s) => Some(s.into()),
                         AdtId::UnionId(u) => Some(u.into()),
                         AdtId::EnumId(_) => None,
                     }),
                 };
+                let variant = variant.map(|variant| names.variant(f.db, variant));
 
-                if let Some(variant) = variant {
-                    match variant {
-                        VariantId::EnumVariantId(v) => {
-                            let data = f.db.enum_data(v.parent);
-                            write!(f, "{}", data.variants[v.local_id].name)?;
-                        }
-                        VariantId::StructId(s) => write!(f, "{}", f.db.struct_data(s).name)?,
-                        VariantId::UnionId(u) => write!(f, "{}", f.db.union_data(u).name)?,
-                    };
+                if let Some((name, variant_data)) = &variant {
+                    write!(f, "{}", name)?;
 
-                    let variant_data = variant.variant_data(f.db.upcast());
-                    if let VariantData::Record(rec_fields) = &*variant_data {
+                    if let VariantData::Record(rec_fields) = &**variant_data {
                         write!(f, " {{ ")?;
 
                         let mut printed = 0;
@@ -335,7 +861,7 @@ fn hir_fmt(&self, f: &mut HirFormatter<'_>) -> Result<(), HirDisplayError> {
                                 printed += 1;
                                 WriteWith(move |f| {
                                     write!(f, "{}: ", rec_fields[p.field].name)?;
-                                    p.pattern.hir_fmt(f)
+                                    p.pattern.display_with_names(names).hir_fmt(f)
                                 })
                             });
                         f.write_joined(subpats, ", ")?;
@@ -349,7 +875,8 @@ fn hir_fmt(&self, f: &mut HirFormatter<'_>) -> Result<(), HirDisplayError> {
                 }
 
                 let num_fields = variant
-                    .map_or(subpatterns.len(), |v| v.variant_data(f.db.upcast()).fields().len());
+                    .as_ref()
+                    .map_or(subpatterns.len(), |(_, variant_data)| variant_data.fields().len());
                 if num_fields != 0 || variant.is_none() {
                     write!(f, "(")?;
                     let subpats = (0..num_fields).map(|i| {
@@ -357,18 +884,18 @@ fn hir_fmt(&self, f: &mut HirFormatter<'_>) -> Result<(), HirDisplayError> {
                             let fid = LocalFieldId::from_raw((i as u32).into());
                             if let Some(p) = subpatterns.get(i) {
                                 if p.field == fid {
-                                    return p.pattern.hir_fmt(f);
+                                    return p.pattern.display_with_names(names).hir_fmt(f);
                                 }
                             }
                             if let Some(p) = subpatterns.iter().find(|p| p.field == fid) {
-                                p.pattern.hir_fmt(f)
+                                p.pattern.display_with_names(names).hir_fmt(f)
                             } else {
                                 write!(f, "_")
                             }
                         })
                     });
                     f.write_joined(subpats, ", ")?;
-                    if let (TyKind::Tuple(..), 1) = (self.ty.kind(Interner), num_fields) {
+                    if let (TyKind::Tuple(..), 1) = (pat.ty.kind(Interner), num_fields) {
                         write!(f, ",")?;
                     }
                     write!(f, ")")?;
@@ -377,17 +904,45 @@ fn hir_fmt(&self, f: &mut HirFormatter<'_>) -> Result<(), HirDisplayError> {
                 Ok(())
             }
             PatKind::Deref { subpattern } => {
-                match self.ty.kind(Interner) {
+                match pat.ty.kind(Interner) {
                     TyKind::Adt(adt, _) if is_box(adt.0, f.db) => write!(f, "box ")?,
                     &TyKind::Ref(mutbl, ..) => {
                         write!(f, "&{}", if mutbl == Mutability::Mut { "mut " } else { "" })?
                     }
-                    _ => never!("{:?} is a bad Deref pattern type", self.ty),
+                    _ => never!("{:?} is a bad Deref pattern type", pat.ty),
                 }
-                subpattern.hir_fmt(f)
+                subpattern.display_with_names(names).hir_fmt(f)
             }
             PatKind::LiteralBool { value } => write!(f, "{}", value),
-            PatKind::Or { pats } => f.write_joined(pats.iter(), " | "),
+            PatKind::Lit { value } => write_literal(f, value),
+            PatKind::Range { lo, hi } => {
+                write_literal(f, lo)?;
+                write!(f, "..=")?;
+                write_literal(f, hi)
+            }
+            PatKind::Slice { prefix, slice, suffix } => {
+                write!(f, "[")?;
+                f.write_joined(prefix.iter().map(|p| p.display_with_names(names)), ", ")?;
+                let mut sep = if prefix.is_empty() { "" } else { ", " };
+                if let Some(slice) = slice {
+                    write!(f, "{sep}")?;
+                    if !matches!(*slice.kind, PatKind::Wild) {
+                        slice.display_with_names(names).hir_fmt(f)?;
+                        write!(f, " @ ")?;
+                    }
+                    write!(f, "..")?;
+                    sep = ", ";
+                }
+                if !suffix.is_empty() {
+                    write!(f, "{sep}")?;
+                    f.write_joined(suffix.iter().map(|p| p.display_with_names(names)), ", ")?;
+                }
+                write!(f, "]")
+            }
+            PatKind::Opaque => write!(f, "_"),
+            PatKind::Or { pats } => {
+                f.write_joined(pats.iter().map(|p| p.display_with_names(names)), " | ")
+            }
         }
     }
 }
@@ -405,6 +960,18 @@ fn hir_fmt(&self, f: &mut HirFormatter<'_>) -> Result<(), HirDisplayError> {
     }
 }
 
+fn write_literal(f: &mut HirFormatter<'_>, lit: &Literal) -> Result<(), HirDisplayError> {
+    match lit {
+        Literal::String(it) => write!(f, "{:?}", it),
+        Literal::ByteString(it) => write!(f, "b\"{}\"", it.escape_ascii()),
+        Literal::Char(it) => write!(f, "{:?}", it),
+        Literal::Bool(it) => write!(f, "{}", it),
+        Literal::Int(it, _) => write!(f, "{}", it),
+        Literal::Uint(it, _) => write!(f, "{}", it),
+        Literal::Float(it, _) => write!(f, "{}", it),
+    }
+}
+
 fn is_box(adt: AdtId, db: &dyn HirDatabase) -> bool {
     let owned_box = name![owned_box].to_smol_str();
     let krate = adt.module(db.upcast()).krate();
@@ -502,7 +1069,88 @@ fn super_fold_with<F: PatternFolder>(&self, folder: &mut F) -> Self {
                 PatKind::Deref { subpattern: subpattern.fold_with(folder) }
             }
             &PatKind::LiteralBool { value } => PatKind::LiteralBool { value },
+            PatKind::Lit { value } => PatKind::Lit { value: value.clone() },
+            PatKind::Range { lo, hi } => PatKind::Range { lo: lo.clone(), hi: hi.clone() },
+            PatKind::Slice { prefix, slice, suffix } => PatKind::Slice {
+                prefix: prefix.fold_with(folder),
+                slice: slice.fold_with(folder),
+                suffix: suffix.fold_with(folder),
+            },
+            PatKind::Opaque => PatKind::Opaque,
             PatKind::Or { pats } => PatKind::Or { pats: pats.fold_with(folder) },
         }
     }
 }
+
+/// Drops the alternatives of or-patterns that come after a wildcard alternative: the wildcard
+/// matches all of their values first, so they are unreachable, e.g. `_ | Some(0)` becomes `_`.
+/// An alternative is only dropped if the wildcard binds all of its bindings, so that the arm body
+/// keeps them. The dropped alternatives are collected in `dropped`.
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct OrPatternSimplifier {
+    pub(crate) dropped: Vec<Pat>,
+}
+
+#[cfg(test)]
+impl PatternFolder for OrPatternSimplifier {
+    fn fold_pattern_kind(&mut self, kind: &PatKind) -> PatKind {
+        let pats = match kind {
+            PatKind::Or { pats } => pats,
+            _ => return kind.super_fold_with(self),
+        };
+        let mut kept: Vec<Pat> = Vec::new();
+        // The bindings of the first wildcard alternative, once we've seen it.
+        let mut wildcard_bindings: Option<Vec<Name>> = None;
+        for pat in pats.fold_with(self) {
+            if let Some(bound) = &wildcard_bindings {
+                if binding_names(&pat).iter().all(|name| bound.contains(name)) {
+                    self.dropped.push(pat);
+                    continue;
+                }
+            } else if matches!(*pat.kind, PatKind::Wild | PatKind::Binding { subpattern: None, .. })
+            {
+                wildcard_bindings = Some(binding_names(&pat));
+            }
+            kept.push(pat);
+        }
+        if kept.len() == 1 {
+            return *kept.pop().unwrap().kind;
+        }
+        PatKind::Or { pats: kept }
+    }
+}
+
+/// The names of the bindings of `pat`, in order.
+#[cfg(test)]
+fn binding_names(pat: &Pat) -> Vec<Name> {
+    fn collect(pat: &Pat, names: &mut Vec<Name>) {
+        match &*pat.kind {
+            PatKind::Binding { name, subpattern } => {
+                names.push(name.clone());
+                if let Some(subpattern) = subpattern {
+                    collect(subpattern, names);
+                }
+            }
+            PatKind::Variant { subpatterns, .. } | PatKind::Leaf { subpatterns } => {
+                subpatterns.iter().for_each(|p| collect(&p.pattern, names))
+            }
+            PatKind::Deref { subpattern } => collect(subpattern, names),
+            PatKind::Slice { prefix, slice, suffix } => {
+                prefix.iter().chain(slice).chain(suffix).for_each(|p| collect(p, names))
+            }
+            PatKind::Or { pats } => pats.iter().for_each(|p| collect(p, names)),
+            PatKind::Wild
+            | PatKind::LiteralBool { .. }
+            | PatKind::Lit { .. }
+            | PatKind::Range { .. }
+            | PatKind::Opaque => {}
+        }
+    }
+    let mut names = Vec::new();
+    collect(pat, &mut names);
+    names
+}
+
+#[cfg(test)]
+mod tests;
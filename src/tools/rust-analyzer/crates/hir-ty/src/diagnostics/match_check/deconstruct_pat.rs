@@ -39,7 +39,8 @@
 //!
 //! Splitting is implemented in the [`Constructor::split`] function. We don't do splitting for
 //! or-patterns; instead we just try the alternatives one-by-one. For details on splitting
-//! wildcards, see [`SplitWildcard`]; for integer ranges, see [`SplitIntRange`].
+//! wildcards, see [`SplitWildcard`]; for integer ranges, see [`SplitIntRange`]; for slices, see
+//! [`SplitVarLenSlice`].
 
 use std::{
     cell::Cell,
@@ -48,13 +49,15 @@
     ops::RangeInclusive,
 };
 
-use hir_def::{EnumVariantId, HasModule, LocalFieldId, VariantId};
+use hir_def::{expr::Literal, intern::Interned, EnumVariantId, HasModule, LocalFieldId, VariantId};
 use smallvec::{smallvec, SmallVec};
 use stdx::never;
 
 use crate::{
-    infer::normalize, inhabitedness::is_enum_variant_uninhabited_from, AdtId, Interner, Scalar, Ty,
-    TyExt, TyKind,
+    infer::normalize,
+    inhabitedness::{is_enum_variant_uninhabited_from, try_usize_const},
+    primitive::{FloatTy, IntTy, UintTy},
+    AdtId, Interner, Scalar, Ty, TyExt, TyKind,
 };
 
 use super::{
@@ -63,7 +66,7 @@
     FieldPat, Pat, PatKind,
 };
 
-use self::Constructor::*;
+use self::{Constructor::*, SliceKind::*};
 
 /// Recursively expand this pattern into its subpatterns. Only useful for or-patterns.
 fn expand_or_pat(pat: &Pat) -> Vec<&Pat> {
@@ -82,10 +85,57 @@ fn expand<'p>(pat: &'p Pat, vec: &mut Vec<&'p Pat>) {
     pats
 }
 
+/// Returns the constructor of the pattern `lo..=hi`, where `pat` is either a literal or a range
+/// pattern. A single-value range like `5..=5` gets the same constructor as the literal `5`.
+fn int_range_ctor(pat: &Pat, lo: &Literal, hi: &Literal) -> Constructor {
+    match IntRange::from_literal_range(lo, hi, &pat.ty) {
+        Some(range) => IntRange(range),
+        // Lowering only gives literal and range patterns to integers, but a malformed one, like a
+        // range against a struct, shouldn't stop the whole body from being checked.
+        None if !IntRange::is_integral(&pat.ty) => {
+            tracing::warn!("literal pattern of a non-integer type: {:?}", pat);
+            Opaque
+        }
+        None => {
+            never!("unexpected literal pattern: {:?}", pat);
+            Opaque
+        }
+    }
+}
+
+/// Returns a pattern matching a single example value of `ty`, or a wildcard if there is no cheap
+/// one. See `DeconstructedPat::to_example_pat`.
+fn example_value(cx: &MatchCheckCtx<'_, '_>, ty: &Ty) -> PatKind {
+    match ty.kind(Interner) {
+        TyKind::Scalar(Scalar::Bool) => PatKind::LiteralBool { value: false },
+        TyKind::Scalar(Scalar::Int(_)) => PatKind::Lit { value: Literal::Int(0, None) },
+        TyKind::Scalar(Scalar::Uint(_)) => PatKind::Lit { value: Literal::Uint(0, None) },
+        TyKind::Adt(AdtId(hir_def::AdtId::EnumId(enum_id)), substs) => {
+            let enum_data = cx.db.enum_data(*enum_id);
+            let variant = enum_data
+                .variants
+                .iter()
+                .map(|(local_id, _)| EnumVariantId { parent: *enum_id, local_id })
+                .find(|&variant| {
+                    !is_enum_variant_uninhabited_from(variant, substs, cx.module, cx.db)
+                });
+            match variant {
+                Some(enum_variant) => PatKind::Variant {
+                    substs: substs.clone(),
+                    enum_variant,
+                    subpatterns: Vec::new(),
+                },
+                None => PatKind::Wild,
+            }
+        }
+        _ => PatKind::Wild,
+    }
+}
+
 /// [Constructor] uses this in umimplemented variants.
 /// It allows porting match expressions from upstream algorithm without losing semantics.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub(super) enum Void {}
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Void {}
 
 /// An inclusive interval, used for precise integer exhaustiveness checking.
 /// `IntRange`s always store a contiguous range. This means that values are
@@ -97,14 +147,14 @@ pub(super) enum Void {}
 ///
 /// `IntRange` is never used to encode an empty range or a "range" that wraps
 /// around the (offset) space: i.e., `range.lo <= range.hi`.
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub(super) struct IntRange {
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct IntRange {
     range: RangeInclusive<u128>,
 }
 
 impl IntRange {
     #[inline]
-    fn is_integral(ty: &Ty) -> bool {
+    pub(super) fn is_integral(ty: &Ty) -> bool {
         matches!(
             ty.kind(Interner),
             TyKind::Scalar(Scalar::Char | Scalar::Int(_) | Scalar::Uint(_) | Scalar::Bool)
@@ -119,17 +169,274 @@ fn boundaries(&self) -> (u128, u128) {
         (*self.range.start(), *self.range.end())
     }
 
+    /// Merges `ranges` of values of the integer type `scalar_ty` into the sorted, disjoint ranges
+    /// of values they cover, and returns them along with the sorted ranges of the values they
+    /// don't cover, as `i128`s. Returns `None` if a bound doesn't fit in an `i128`.
+    #[cfg(test)]
+    pub(super) fn coverage(
+        mut ranges: Vec<IntRange>,
+        scalar_ty: Scalar,
+    ) -> Option<(Vec<(i128, i128)>, Vec<(i128, i128)>)> {
+        ranges.sort_by_key(IntRange::boundaries);
+        let mut covered: Vec<(u128, u128)> = Vec::new();
+        for (lo, hi) in ranges.iter().map(IntRange::boundaries) {
+            match covered.last_mut() {
+                Some((_, last_hi)) if lo <= last_hi.saturating_add(1) => {
+                    *last_hi = max(*last_hi, hi)
+                }
+                _ => covered.push((lo, hi)),
+            }
+        }
+
+        let max_bits = IntRange::truncate(u128::MAX, scalar_ty);
+        let mut gaps = Vec::new();
+        // The first value that is past all the covered ranges seen so far, if any.
+        let mut next = Some(0);
+        for &(lo, hi) in &covered {
+            match next {
+                Some(next) if next < lo => gaps.push((next, lo - 1)),
+                _ => {}
+            }
+            next = hi.checked_add(1);
+        }
+        match next {
+            Some(next) if next <= max_bits => gaps.push((next, max_bits)),
+            _ => {}
+        }
+
+        let to_i128_ranges = |ranges: Vec<(u128, u128)>| {
+            ranges
+                .into_iter()
+                .map(|(lo, hi)| {
+                    Some((
+                        IntRange::bits_to_i128(lo, scalar_ty)?,
+                        IntRange::bits_to_i128(hi, scalar_ty)?,
+                    ))
+                })
+                .collect()
+        };
+        Some((to_i128_ranges(covered)?, to_i128_ranges(gaps)?))
+    }
+
+    /// Splits the values of the integer type `scalar_ty` that ranges of more than one arm contain
+    /// into sorted, disjoint ranges, each with the sorted indices of the arms containing it. `ranges`
+    /// pairs each range with the index of its arm. Returns `None` if a bound doesn't fit in an
+    /// `i128`.
+    #[cfg(test)]
+    pub(super) fn overlap_map(
+        ranges: &[(usize, IntRange)],
+        scalar_ty: Scalar,
+    ) -> Option<Vec<((i128, i128), Vec<usize>)>> {
+        // The values at which the set of ranges containing a value can change.
+        let mut starts: Vec<u128> = ranges
+            .iter()
+            .flat_map(|(_, range)| {
+                let (lo, hi) = range.boundaries();
+                once(lo).chain(hi.checked_add(1))
+            })
+            .collect();
+        starts.sort_unstable();
+        starts.dedup();
+
+        let mut overlaps: Vec<((u128, u128), Vec<usize>)> = Vec::new();
+        for (idx, &start) in starts.iter().enumerate() {
+            let end = starts.get(idx + 1).map_or(u128::MAX, |next| next - 1);
+            let mut arms: Vec<usize> = ranges
+                .iter()
+                .filter(|(_, range)| range.range.contains(&start))
+                .map(|&(arm, _)| arm)
+                .collect();
+            arms.sort_unstable();
+            arms.dedup();
+            if arms.len() < 2 {
+                continue;
+            }
+            match overlaps.last_mut() {
+                Some(((_, last_end), last_arms))
+                    if *last_end + 1 == start && *last_arms == arms =>
+                {
+                    *last_end = end
+                }
+                _ => overlaps.push(((start, end), arms)),
+            }
+        }
+
+        overlaps
+            .into_iter()
+            .map(|((lo, hi), arms)| {
+                Some((
+                    (
+                        IntRange::bits_to_i128(lo, scalar_ty)?,
+                        IntRange::bits_to_i128(hi, scalar_ty)?,
+                    ),
+                    arms,
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the values that `self` and `other` both contain, as `i128`s, if they overlap
+    /// without one of them containing the other, like `0..=200` and `100..=255`.
+    #[cfg(test)]
+    pub(super) fn partial_overlap(&self, other: &Self, scalar_ty: Scalar) -> Option<(i128, i128)> {
+        if self.is_subrange(other) || other.is_subrange(self) {
+            return None;
+        }
+        let (lo, hi) = self.intersection(other)?.boundaries();
+        Some((IntRange::bits_to_i128(lo, scalar_ty)?, IntRange::bits_to_i128(hi, scalar_ty)?))
+    }
+
+    /// Decodes the (offset) bits of a range boundary into the integer value they stand for.
+    #[cfg(test)]
+    fn bits_to_i128(bits: u128, scalar_ty: Scalar) -> Option<i128> {
+        let bias = IntRange::signed_bias(scalar_ty);
+        match IntRange::bits_to_literal(bits ^ bias, scalar_ty)? {
+            Literal::Int(it, _) => Some(it),
+            Literal::Uint(it, _) => i128::try_from(it).ok(),
+            _ => None,
+        }
+    }
+
     #[inline]
     fn from_bool(value: bool) -> IntRange {
         let val = value as u128;
         IntRange { range: val..=val }
     }
 
+    /// Creates the range of a literal pattern of type `ty`. Returns `None` if the literal does not
+    /// fit in `ty`.
+    pub(super) fn from_literal(lit: &Literal, ty: &Ty) -> Option<IntRange> {
+        IntRange::from_literal_range(lit, lit, ty)
+    }
+
+    /// Creates the range of the pattern `lo..=hi` of type `ty`. Returns `None` if a bound does not
+    /// fit in `ty`, or if the range is empty.
+    pub(super) fn from_literal_range(lo: &Literal, hi: &Literal, ty: &Ty) -> Option<IntRange> {
+        match ty.kind(Interner) {
+            &TyKind::Scalar(scalar_ty) => IntRange::from_literals(lo, hi, scalar_ty),
+            _ => None,
+        }
+    }
+
+    fn from_literals(lo: &Literal, hi: &Literal, scalar_ty: Scalar) -> Option<IntRange> {
+        let lo = IntRange::literal_bits(lo, scalar_ty)?;
+        let hi = IntRange::literal_bits(hi, scalar_ty)?;
+        let range = IntRange::from_range(lo, hi, scalar_ty);
+        (range.range.start() <= range.range.end()).then(|| range)
+    }
+
+    /// Returns the value right before `lit` in `ty`, or `None` if `lit` is the smallest value of
+    /// `ty` or does not fit in it.
+    pub(super) fn literal_before(lit: &Literal, ty: &Ty) -> Option<Literal> {
+        let scalar_ty = match ty.kind(Interner) {
+            &TyKind::Scalar(scalar_ty) => scalar_ty,
+            _ => return None,
+        };
+        let bias = IntRange::signed_bias(scalar_ty);
+        let before = (IntRange::literal_bits(lit, scalar_ty)? ^ bias).checked_sub(1)?;
+        let before = match scalar_ty {
+            // Skip the surrogate code points, which aren't `char`s.
+            Scalar::Char if (0xD800..=0xDFFF).contains(&before) => 0xD7FF,
+            _ => before,
+        };
+        IntRange::bits_to_literal(before ^ bias, scalar_ty)
+    }
+
     #[inline]
     fn from_range(lo: u128, hi: u128, scalar_ty: Scalar) -> IntRange {
+        // Perform a shift if the underlying types are signed,
+        // which makes the interval arithmetic simpler.
+        let bias = IntRange::signed_bias(scalar_ty);
+        IntRange { range: (lo ^ bias)..=(hi ^ bias) }
+    }
+
+    /// Returns the range of all the values of the integer type `scalar_ty`.
+    fn full(scalar_ty: Scalar) -> IntRange {
+        match IntRange::signed_bias(scalar_ty) {
+            0 => IntRange::from_range(0, IntRange::truncate(u128::MAX, scalar_ty), scalar_ty),
+            // `bias` is the bit representation of `MIN`, and `bias - 1` that of `MAX`.
+            bias => IntRange::from_range(bias, bias - 1, scalar_ty),
+        }
+    }
+
+    /// Returns the range of all the values of `isize` or `usize` on a target whose pointers are
+    /// `width` bits wide. Both are represented as 64-bit integers, so this is a subrange of theirs
+    /// for narrower targets.
+    fn pointer_sized(scalar_ty: Scalar, width: u32) -> IntRange {
+        let width = width.min(IntRange::repr_size(scalar_ty));
+        let mask = IntRange::truncate(u128::MAX, scalar_ty);
+        let (lo, hi) = match scalar_ty {
+            Scalar::Int(_) => (-(1i128 << (width - 1)), (1i128 << (width - 1)) - 1),
+            _ => (0, (1i128 << width) - 1),
+        };
+        IntRange::from_range(lo as u128 & mask, hi as u128 & mask, scalar_ty)
+    }
+
+    /// The width of `scalar_ty` in bits, or `None` for `isize` and `usize`, whose width is the
+    /// pointer width of the target.
+    fn size(scalar_ty: Scalar) -> Option<u32> {
+        let size = match scalar_ty {
+            Scalar::Bool => 1,
+            Scalar::Int(IntTy::I8) | Scalar::Uint(UintTy::U8) => 8,
+            Scalar::Int(IntTy::I16) | Scalar::Uint(UintTy::U16) => 16,
+            Scalar::Char
+            | Scalar::Int(IntTy::I32)
+            | Scalar::Uint(UintTy::U32)
+            | Scalar::Float(FloatTy::F32) => 32,
+            Scalar::Int(IntTy::I64) | Scalar::Uint(UintTy::U64) | Scalar::Float(FloatTy::F64) => 64,
+            Scalar::Int(IntTy::I128) | Scalar::Uint(UintTy::U128) => 128,
+            Scalar::Int(IntTy::Isize) | Scalar::Uint(UintTy::Usize) => return None,
+        };
+        Some(size)
+    }
+
+    /// The number of bits that represent the values of `scalar_ty` in a range. `isize` and `usize`
+    /// get 64 bits, enough for the pointers of any target; `pointer_sized` narrows their values
+    /// down to one target.
+    fn repr_size(scalar_ty: Scalar) -> u32 {
+        IntRange::size(scalar_ty).unwrap_or(64)
+    }
+
+    /// Keeps the low bits of `bits` that fit in `scalar_ty`.
+    fn truncate(bits: u128, scalar_ty: Scalar) -> u128 {
+        bits & (u128::MAX >> (128 - IntRange::repr_size(scalar_ty)))
+    }
+
+    // The return value of `signed_bias` should be XORed with an endpoint to encode/decode it.
+    fn signed_bias(scalar_ty: Scalar) -> u128 {
+        match scalar_ty {
+            Scalar::Int(_) => 1u128 << (IntRange::repr_size(scalar_ty) - 1),
+            _ => 0,
+        }
+    }
+
+    /// Returns the bit representation of `lit` as a value of type `scalar_ty`, or `None` if it
+    /// isn't a value of that type.
+    fn literal_bits(lit: &Literal, scalar_ty: Scalar) -> Option<u128> {
+        let shift = 128 - IntRange::repr_size(scalar_ty);
+        let signed =
+            |it: i128| ((it << shift) >> shift == it).then(|| it as u128 & (u128::MAX >> shift));
+        let unsigned = |it: u128| ((it << shift) >> shift == it).then(|| it);
+        match (lit, scalar_ty) {
+            (&Literal::Bool(it), Scalar::Bool) => Some(it as u128),
+            (&Literal::Char(it), Scalar::Char) => Some(it as u128),
+            (&Literal::Int(it, _), Scalar::Int(_)) => signed(it),
+            (&Literal::Uint(it, _), Scalar::Int(_)) => signed(i128::try_from(it).ok()?),
+            (&Literal::Int(it, _), Scalar::Uint(_)) => unsigned(u128::try_from(it).ok()?),
+            (&Literal::Uint(it, _), Scalar::Uint(_)) => unsigned(it),
+            _ => None,
+        }
+    }
+
+    /// Converts the bit representation of a value of type `scalar_ty` back to a literal.
+    fn bits_to_literal(bits: u128, scalar_ty: Scalar) -> Option<Literal> {
+        let shift = 128 - IntRange::repr_size(scalar_ty);
         match scalar_ty {
-            Scalar::Bool => IntRange { range: lo..=hi },
-            _ => unimplemented!(),
+            Scalar::Char => u32::try_from(bits).ok().and_then(char::from_u32).map(Literal::Char),
+            // Sign-extend the bits.
+            Scalar::Int(_) => Some(Literal::Int(((bits << shift) as i128) >> shift, None)),
+            Scalar::Uint(_) => Some(Literal::Uint(bits, None)),
+            _ => None,
         }
     }
 
@@ -148,7 +455,7 @@ fn intersection(&self, other: &Self) -> Option<Self> {
     }
 
     fn to_pat(&self, _cx: &MatchCheckCtx<'_, '_>, ty: Ty) -> Pat {
-        match ty.kind(Interner) {
+        let scalar_ty = match ty.kind(Interner) {
             TyKind::Scalar(Scalar::Bool) => {
                 let kind = match self.boundaries() {
                     (0, 0) => PatKind::LiteralBool { value: false },
@@ -159,10 +466,29 @@ fn to_pat(&self, _cx: &MatchCheckCtx<'_, '_>, ty: Ty) -> Pat {
                         PatKind::Wild
                     }
                 };
-                Pat { ty, kind: kind.into() }
+                return Pat { ty, kind: kind.into() };
             }
-            _ => unimplemented!(),
-        }
+            &TyKind::Scalar(scalar_ty) => scalar_ty,
+            _ => {
+                never!("bad type for int range pattern: {:?}", ty);
+                return Pat { ty, kind: PatKind::Wild.into() };
+            }
+        };
+
+        let (lo, hi) = self.boundaries();
+        let bias = IntRange::signed_bias(scalar_ty);
+        let kind = match (
+            IntRange::bits_to_literal(lo ^ bias, scalar_ty),
+            IntRange::bits_to_literal(hi ^ bias, scalar_ty),
+        ) {
+            (Some(lo), Some(hi)) if lo == hi => PatKind::Lit { value: lo },
+            (Some(lo), Some(hi)) => PatKind::Range { lo, hi },
+            _ => {
+                never!("bad range for {:?} pattern: {}..={}", scalar_ty, lo, hi);
+                PatKind::Wild
+            }
+        };
+        Pat { ty, kind: kind.into() }
     }
 
     /// See `Constructor::is_covered_by`
@@ -273,20 +599,195 @@ fn iter(&self) -> impl Iterator<Item = IntRange> + '_ {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum SliceKind {
+    /// Patterns of length `n` (`[x, y]`).
+    FixedLen(usize),
+    /// Patterns using the `..` notation (`[x, .., y]`).
+    /// Captures any array constructor of `length >= i + j`.
+    /// In the case where `array_len` is `Some(_)`,
+    /// this indicates that we only care about the first `i` and the last `j` values of the array,
+    /// and everything in between is a wildcard `_`.
+    VarLen(usize, usize),
+}
+
+impl SliceKind {
+    fn arity(self) -> usize {
+        match self {
+            FixedLen(length) => length,
+            VarLen(prefix, suffix) => prefix + suffix,
+        }
+    }
+
+    /// Whether this pattern includes patterns of length `other_len`.
+    fn covers_length(self, other_len: usize) -> bool {
+        match self {
+            FixedLen(len) => len == other_len,
+            VarLen(prefix, suffix) => prefix + suffix <= other_len,
+        }
+    }
+}
+
 /// A constructor for array and slice patterns.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub(super) struct Slice {
-    _unimplemented: Void,
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct Slice {
+    /// `None` if the matched value is a slice (or an array of unknown length), `Some(n)` if it is
+    /// an array of size `n`.
+    array_len: Option<usize>,
+    /// The kind of pattern it is: fixed-length `[x, y]` or variable length `[x, .., y]`.
+    kind: SliceKind,
 }
 
 impl Slice {
+    fn new(array_len: Option<usize>, kind: SliceKind) -> Self {
+        let kind = match (array_len, kind) {
+            // If the middle `..` is empty, we effectively have a fixed-length pattern.
+            (Some(len), VarLen(prefix, suffix)) if prefix + suffix >= len => FixedLen(len),
+            _ => kind,
+        };
+        Slice { array_len, kind }
+    }
+
     fn arity(self) -> usize {
-        match self._unimplemented {}
+        self.kind.arity()
     }
 
     /// See `Constructor::is_covered_by`
-    fn is_covered_by(self, _other: Self) -> bool {
-        match self._unimplemented {}
+    fn is_covered_by(self, other: Self) -> bool {
+        other.kind.covers_length(self.arity())
+    }
+}
+
+/// This computes constructor splitting for variable-length slices, as explained at the top of the
+/// file.
+///
+/// A slice pattern `[x, .., y]` behaves like the infinite or-pattern `[x, y] | [x, _, y] | [x, _,
+/// _, y] | ...`. The corresponding value constructors are fixed-length array constructors above a
+/// given minimum length. We obviously can't list this infinitude of constructors. Thankfully,
+/// it turns out that for each finite set of slice patterns, all sufficiently large array lengths
+/// are equivalent.
+///
+/// Let's look at an example, where we are trying to split the last pattern:
+/// ```
+/// match x {
+///     [true, true, ..] => {}
+///     [.., false, false] => {}
+///     [..] => {}
+/// }
+/// ```
+/// Here are the results of specialization for the first few lengths:
+/// ```
+/// // length 0
+/// [] => {}
+/// // length 1
+/// [_] => {} // `_` can match `true` or `false`
+/// // length 2
+/// [true, true] => {}
+/// [false, false] => {}
+/// [_, _] => {}
+/// // length 3
+/// [true, true,  _    ] => {}
+/// [_,    false, false] => {}
+/// [_,    _,     _    ] => {}
+/// // length 4
+/// [true, true, _,     _    ] => {}
+/// [_,    _,    false, false] => {}
+/// [_,    _,    _,     _    ] => {}
+/// // length 5
+/// [true, true, _, _,     _    ] => {}
+/// [_,    _,    _, false, false] => {}
+/// [_,    _,    _, _,     _    ] => {}
+/// ```
+///
+/// If we went above length 5, we would simply be inserting more columns full of wildcards in the
+/// middle. This means that the set of witnesses for length `l >= 5` if equivalent to the set for
+/// any other `l' >= 5`: simply add or remove wildcards in the middle to convert between them.
+///
+/// This applies to any set of slice patterns: there will be a length `L` above which all lengths
+/// behave the same. This is exactly what we need for constructor splitting. Therefore a
+/// variable-length slice can be split into a variable-length slice of minimal length `L`, and many
+/// fixed-length slices of lengths `< L`.
+///
+/// For each variable-length pattern `p` with a prefix of length `plₚ` and suffix of length `slₚ`,
+/// only the first `plₚ` and the last `slₚ` elements are examined. Therefore, as long as `L` is
+/// positive (to avoid concerns about empty types), all elements after the maximum prefix length
+/// and before the maximum suffix length are not examined by any variable-length pattern, and
+/// therefore can be added/removed without affecting them - creating equivalent patterns from any
+/// sufficiently-large length.
+///
+/// Of course, if fixed-length patterns exist, we must be sure that our length is large enough to
+/// miss them all, so we can pick `L = max(max(FIXED_LEN)+1, max(PREFIX_LEN) + max(SUFFIX_LEN))`
+///
+/// `max_slice` below will be made to have arity `L`.
+#[derive(Debug)]
+struct SplitVarLenSlice {
+    /// If the type is an array, this is its size.
+    array_len: Option<usize>,
+    /// The arity of the input slice.
+    arity: usize,
+    /// The smallest slice bigger than any slice seen. `max_slice.arity()` is the length `L`
+    /// described above.
+    max_slice: SliceKind,
+}
+
+impl SplitVarLenSlice {
+    fn new(prefix: usize, suffix: usize, array_len: Option<usize>) -> Self {
+        SplitVarLenSlice { array_len, arity: prefix + suffix, max_slice: VarLen(prefix, suffix) }
+    }
+
+    /// Pass a set of slices relative to which to split this one.
+    fn split(&mut self, slices: impl Iterator<Item = SliceKind>) {
+        let (max_prefix_len, max_suffix_len) = match &mut self.max_slice {
+            VarLen(prefix, suffix) => (prefix, suffix),
+            // No need to split
+            FixedLen(_) => return,
+        };
+        // We grow `self.max_slice` to be larger than all slices encountered, as described above.
+        // For diagnostics, we keep the prefix and suffix lengths separate, but grow them so that
+        // `L = max_prefix_len + max_suffix_len`.
+        let mut max_fixed_len = 0;
+        for slice in slices {
+            match slice {
+                FixedLen(len) => {
+                    max_fixed_len = max(max_fixed_len, len);
+                }
+                VarLen(prefix, suffix) => {
+                    *max_prefix_len = max(*max_prefix_len, prefix);
+                    *max_suffix_len = max(*max_suffix_len, suffix);
+                }
+            }
+        }
+        // We want `L = max(L, max_fixed_len + 1)`, modulo the fact that we keep prefix and
+        // suffix separate.
+        if max_fixed_len + 1 >= *max_prefix_len + *max_suffix_len {
+            // The subtraction can't overflow thanks to the above check.
+            // The new `max_prefix_len` is larger than its previous value.
+            *max_prefix_len = max_fixed_len + 1 - *max_suffix_len;
+        }
+
+        // We cap the arity of `max_slice` at the array size.
+        match self.array_len {
+            Some(len) if self.max_slice.arity() >= len => self.max_slice = FixedLen(len),
+            _ => {}
+        }
+    }
+
+    /// Iterate over the partition of this slice.
+    fn iter(&self) -> impl Iterator<Item = Slice> + Captures<'_> {
+        let smaller_lengths = match self.array_len {
+            // The only admissible fixed-length slice is one of the array size. Whether `max_slice`
+            // is fixed-length or variable-length, it will be the only relevant slice to output
+            // here.
+            Some(_) => 0..0, // empty range
+            // We cover all arities in the range `(self.arity..infinity)`. We split that range into
+            // two: lengths smaller than `max_slice.arity()` are treated independently as
+            // fixed-lengths slices, and lengths above are captured by `max_slice`.
+            None => self.arity..self.max_slice.arity(),
+        };
+        smaller_lengths
+            .map(FixedLen)
+            .chain(once(self.max_slice))
+            .map(move |kind| Slice::new(self.array_len, kind))
     }
 }
 
@@ -298,8 +799,8 @@ fn is_covered_by(self, _other: Self) -> bool {
 /// constructor. `Constructor::apply` reconstructs the pattern from a pair of `Constructor` and
 /// `Fields`.
 #[allow(dead_code)]
-#[derive(Clone, Debug, PartialEq)]
-pub(super) enum Constructor {
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Constructor {
     /// The constructor for patterns that have a single constructor, like tuples, struct patterns
     /// and fixed-length arrays.
     Single,
@@ -310,7 +811,8 @@ pub(super) enum Constructor {
     /// Ranges of floating-point literal values (`2.0..=5.2`).
     FloatRange(Void),
     /// String literals. Strings are not quite the same as `&[u8]` so we treat them separately.
-    Str(Void),
+    /// They are interned, so that comparing them doesn't compare their contents.
+    Str(Interned<str>),
     /// Array and slice patterns.
     Slice(Slice),
     /// Constants that must not be matched structurally. They are treated as black
@@ -323,8 +825,13 @@ pub(super) enum Constructor {
     /// Stands for constructors that are not seen in the matrix, as explained in the documentation
     /// for [`SplitWildcard`]. The carried `bool` is used for the `non_exhaustive_omitted_patterns`
     /// lint.
+    ///
+    /// This is a synthetic constructor: it is only ever produced by splitting a wildcard, and is
+    /// then used to specialize the matrix and to build witnesses. It never comes from a user
+    /// pattern, so it must never appear in the matrix itself; `Matrix::push` checks this.
     Missing { nonexhaustive_enum_missing_real_variants: bool },
-    /// Wildcard pattern.
+    /// Wildcard pattern. Unlike `Missing`, this comes from user patterns like `_` or bindings, and
+    /// covers every constructor of its type.
     Wildcard,
     /// Or-pattern.
     Or,
@@ -335,10 +842,22 @@ pub(super) fn is_wildcard(&self) -> bool {
         matches!(self, Wildcard)
     }
 
+    pub(super) fn is_missing(&self) -> bool {
+        matches!(self, Missing { .. })
+    }
+
     pub(super) fn is_non_exhaustive(&self) -> bool {
         matches!(self, NonExhaustive)
     }
 
+    /// The enum variant of this constructor, if it is one.
+    pub(crate) fn as_variant(&self) -> Option<EnumVariantId> {
+        match *self {
+            Variant(id) => Some(id),
+            _ => None,
+        }
+    }
+
     fn as_int_range(&self) -> Option<&IntRange> {
         match self {
             IntRange(range) => Some(range),
@@ -362,8 +881,10 @@ pub(super) fn is_doc_hidden_variant(&self, _pcx: PatCtxt<'_, '_>) -> bool {
     }
 
     fn variant_id_for_adt(&self, adt: hir_def::AdtId) -> VariantId {
+        if let Some(id) = self.as_variant() {
+            return id.into();
+        }
         match *self {
-            Variant(id) => id.into(),
             Single => {
                 assert!(!matches!(adt, hir_def::AdtId::EnumId(_)));
                 match adt {
@@ -432,22 +953,37 @@ pub(super) fn split<'a>(
         ctors: impl Iterator<Item = &'a Constructor> + Clone,
     ) -> SmallVec<[Self; 1]> {
         match self {
+            // These constructors split into themselves whatever the matrix contains, so `ctors`
+            // isn't even looked at. This is the common case in matches on enums.
+            Single
+            | Variant(_)
+            | FloatRange(_)
+            | Str(_)
+            | Slice(Slice { kind: FixedLen(_), .. })
+            | Opaque
+            | NonExhaustive
+            | Missing { .. }
+            | Or => smallvec![self.clone()],
+            // Fast-track if the range is trivial. In particular, we don't do the overlapping
+            // ranges check.
+            IntRange(ctor_range) if ctor_range.is_singleton() => smallvec![self.clone()],
             Wildcard => {
                 let mut split_wildcard = SplitWildcard::new(pcx);
                 split_wildcard.split(pcx, ctors);
                 split_wildcard.into_ctors(pcx)
             }
-            // Fast-track if the range is trivial. In particular, we don't do the overlapping
-            // ranges check.
-            IntRange(ctor_range) if !ctor_range.is_singleton() => {
+            IntRange(ctor_range) => {
                 let mut split_range = SplitIntRange::new(ctor_range.clone());
                 let int_ranges = ctors.filter_map(|ctor| ctor.as_int_range());
                 split_range.split(int_ranges.cloned());
                 split_range.iter().map(IntRange).collect()
             }
-            Slice(slice) => match slice._unimplemented {},
-            // Any other constructor can be used unchanged.
-            _ => smallvec![self.clone()],
+            &Slice(Slice { kind: VarLen(self_prefix, self_suffix), array_len }) => {
+                let mut split_self = SplitVarLenSlice::new(self_prefix, self_suffix, array_len);
+                let slices = ctors.filter_map(|c| c.as_slice()).map(|s| s.kind);
+                split_self.split(slices);
+                split_self.iter().map(Slice).collect()
+            }
         }
     }
 
@@ -469,7 +1005,7 @@ pub(super) fn is_covered_by(&self, _pcx: PatCtxt<'_, '_>, other: &Self) -> bool
 
             (IntRange(self_range), IntRange(other_range)) => self_range.is_covered_by(other_range),
             (FloatRange(void), FloatRange(..)) => match *void {},
-            (Str(void), Str(..)) => match *void {},
+            (Str(self_val), Str(other_val)) => self_val == other_val,
             (Slice(self_slice), Slice(other_slice)) => self_slice.is_covered_by(*other_slice),
 
             // We are trying to inspect an opaque constant. Thus we skip the row.
@@ -558,8 +1094,19 @@ pub(super) fn new(pcx: PatCtxt<'_, '_>) -> Self {
         // `cx.is_uninhabited()`).
         let all_ctors = match pcx.ty.kind(Interner) {
             TyKind::Scalar(Scalar::Bool) => smallvec![make_range(0, 1, Scalar::Bool)],
-            // TyKind::Array(..) if ... => unhandled(),
-            TyKind::Array(..) | TyKind::Slice(..) => unhandled(),
+            TyKind::Array(sub_ty, len) if try_usize_const(len).is_some() => {
+                let len = try_usize_const(len).unwrap() as usize;
+                if len != 0 && cx.is_uninhabited(sub_ty) {
+                    SmallVec::new()
+                } else {
+                    smallvec![Slice(Slice::new(Some(len), VarLen(0, 0)))]
+                }
+            }
+            // Treat arrays of a constant but unknown length like slices.
+            TyKind::Array(sub_ty, _) | TyKind::Slice(sub_ty) => {
+                let kind = if cx.is_uninhabited(sub_ty) { FixedLen(0) } else { VarLen(0, 0) };
+                smallvec![Slice(Slice::new(None, kind))]
+            }
             TyKind::Adt(AdtId(hir_def::AdtId::EnumId(enum_id)), subst) => {
                 let enum_data = cx.db.enum_data(*enum_id);
 
@@ -610,12 +1157,42 @@ pub(super) fn new(pcx: PatCtxt<'_, '_>) -> Self {
                 }
                 ctors
             }
-            TyKind::Scalar(Scalar::Char) => unhandled(),
-            TyKind::Scalar(Scalar::Int(..) | Scalar::Uint(..)) => unhandled(),
+            TyKind::Scalar(Scalar::Char) => smallvec![
+                // The valid Unicode Scalar Value ranges.
+                make_range('\u{0000}' as u128, '\u{D7FF}' as u128, Scalar::Char),
+                make_range('\u{E000}' as u128, '\u{10FFFF}' as u128, Scalar::Char),
+            ],
+            // `isize` and `usize` are not treated exhaustively, as their range depends on the
+            // target, unless the crate has a `target_pointer_width` cfg.
+            &TyKind::Scalar(
+                scalar_ty @ (Scalar::Int(IntTy::Isize) | Scalar::Uint(UintTy::Usize)),
+            ) => match cx.config.target_pointer_width {
+                Some(width) => smallvec![IntRange(IntRange::pointer_sized(scalar_ty, width))],
+                None => unhandled(),
+            },
+            &TyKind::Scalar(scalar_ty @ (Scalar::Int(_) | Scalar::Uint(_))) => {
+                smallvec![IntRange(IntRange::full(scalar_ty))]
+            }
             TyKind::Never if !cx.feature_exhaustive_patterns() && !pcx.is_top_level => {
                 smallvec![NonExhaustive]
             }
             TyKind::Never => SmallVec::new(),
+            // We can't list the constructors of a type parameter, so only a wildcard or a binding
+            // can cover it.
+            TyKind::Placeholder(..) => unhandled(),
+            // Neither can we for types we can't see through, like a projection we failed to
+            // normalize (e.g. the owned type of a `Cow<str>`), opaque types or trait objects.
+            TyKind::Alias(_)
+            | TyKind::AssociatedType(..)
+            | TyKind::OpaqueType(..)
+            | TyKind::Dyn(_)
+            | TyKind::Foreign(_) => unhandled(),
+            // Inference should have resolved every type by now, but if it didn't we know nothing
+            // about the type.
+            TyKind::InferenceVar(..) => {
+                tracing::warn!("unresolved type {:?} in a match", pcx.ty);
+                unhandled()
+            }
             _ if cx.is_uninhabited(pcx.ty) => SmallVec::new(),
             TyKind::Adt(..) | TyKind::Tuple(..) | TyKind::Ref(..) => smallvec![Single],
             // This type is one for which we cannot list constructors, like `str` or `f64`.
@@ -651,6 +1228,24 @@ pub(super) fn iter_missing<'a, 'p>(
         self.all_ctors.iter().filter(move |ctor| !ctor.is_covered_by_any(pcx, &self.matrix_ctors))
     }
 
+    /// Whether none of the value constructors for this type are present in the matrix, so the
+    /// missing constructors are all of them. A witness for them is then `_` rather than a list of
+    /// specific constructors. This also holds for empty types, which have no constructors at all.
+    #[cfg(test)]
+    pub(super) fn all_missing(&self, pcx: PatCtxt<'_, '_>) -> bool {
+        self.iter_present(pcx).next().is_none()
+    }
+
+    /// Iterate over the constructors for this type that are present in the matrix. Together with
+    /// `iter_missing`, this partitions the constructors of the type.
+    #[cfg(test)]
+    pub(super) fn iter_present<'a, 'p>(
+        &'a self,
+        pcx: PatCtxt<'a, 'p>,
+    ) -> impl Iterator<Item = &'a Constructor> + Captures<'p> {
+        self.all_ctors.iter().filter(move |ctor| ctor.is_covered_by_any(pcx, &self.matrix_ctors))
+    }
+
     /// Return the set of constructors resulting from splitting the wildcard. As explained at the
     /// top of the file, if any constructors are missing we can ignore the present ones.
     fn into_ctors(self, pcx: PatCtxt<'_, '_>) -> SmallVec<[Constructor; 1]> {
@@ -731,7 +1326,7 @@ pub(super) struct Fields<'p> {
 }
 
 impl<'p> Fields<'p> {
-    fn empty() -> Self {
+    pub(super) fn empty() -> Self {
         Fields { fields: &[] }
     }
 
@@ -793,31 +1388,26 @@ pub(crate) fn wildcards(
         constructor: &Constructor,
     ) -> Self {
         let ret = match constructor {
-            Single | Variant(_) => match ty.kind(Interner) {
-                TyKind::Tuple(_, substs) => {
-                    let tys = substs.iter(Interner).map(|ty| ty.assert_ty_ref(Interner));
-                    Fields::wildcards_from_tys(cx, tys.cloned())
+            Single | Variant(_) => {
+                let key = (ty.clone(), constructor.clone());
+                if let Some(&fields) = cx.wildcards_cache.borrow().get(&key) {
+                    cov_mark::hit!(match_check_wildcards_cached);
+                    return fields;
                 }
-                TyKind::Ref(.., rty) => Fields::wildcards_from_tys(cx, once(rty.clone())),
-                &TyKind::Adt(AdtId(adt), ref substs) => {
-                    if is_box(adt, cx.db) {
-                        // The only legal patterns of type `Box` (outside `std`) are `_` and box
-                        // patterns. If we're here we can assume this is a box pattern.
-                        let subst_ty = substs.at(Interner, 0).assert_ty_ref(Interner).clone();
-                        Fields::wildcards_from_tys(cx, once(subst_ty))
-                    } else {
-                        let variant = constructor.variant_id_for_adt(adt);
-                        let tys = Fields::list_variant_nonhidden_fields(cx, ty, variant)
-                            .map(|(_, ty)| ty);
-                        Fields::wildcards_from_tys(cx, tys)
-                    }
+                let fields = Fields::wildcards_uncached(cx, ty, constructor);
+                cx.wildcards_cache.borrow_mut().insert(key, fields);
+                fields
+            }
+            Slice(slice) => match ty.kind(Interner) {
+                TyKind::Slice(elem_ty) | TyKind::Array(elem_ty, _) => {
+                    let arity = slice.arity();
+                    Fields::wildcards_from_tys(cx, (0..arity).map(|_| elem_ty.clone()))
                 }
-                ty_kind => {
-                    never!("Unexpected type for `Single` constructor: {:?}", ty_kind);
-                    Fields::wildcards_from_tys(cx, once(ty.clone()))
+                _ => {
+                    never!("bad slice pattern {:?} {:?}", constructor, ty);
+                    Fields::empty()
                 }
             },
-            Slice(slice) => match slice._unimplemented {},
             Str(..)
             | FloatRange(..)
             | IntRange(..)
@@ -833,6 +1423,33 @@ pub(crate) fn wildcards(
         ret
     }
 
+    fn wildcards_uncached(cx: &MatchCheckCtx<'_, 'p>, ty: &Ty, constructor: &Constructor) -> Self {
+        match ty.kind(Interner) {
+            TyKind::Tuple(_, substs) => {
+                let tys = substs.iter(Interner).map(|ty| ty.assert_ty_ref(Interner));
+                Fields::wildcards_from_tys(cx, tys.cloned())
+            }
+            TyKind::Ref(.., rty) => Fields::wildcards_from_tys(cx, once(rty.clone())),
+            &TyKind::Adt(AdtId(adt), ref substs) => {
+                if is_box(adt, cx.db) {
+                    // The only legal patterns of type `Box` (outside `std`) are `_` and box
+                    // patterns. If we're here we can assume this is a box pattern.
+                    let subst_ty = substs.at(Interner, 0).assert_ty_ref(Interner).clone();
+                    Fields::wildcards_from_tys(cx, once(subst_ty))
+                } else {
+                    let variant = constructor.variant_id_for_adt(adt);
+                    let tys =
+                        Fields::list_variant_nonhidden_fields(cx, ty, variant).map(|(_, ty)| ty);
+                    Fields::wildcards_from_tys(cx, tys)
+                }
+            }
+            ty_kind => {
+                never!("Unexpected type for `Single` constructor: {:?}", ty_kind);
+                Fields::wildcards_from_tys(cx, once(ty.clone()))
+            }
+        }
+    }
+
     /// Returns the list of patterns.
     pub(super) fn iter_patterns<'a>(
         &'a self,
@@ -966,8 +1583,48 @@ pub(crate) fn from_pat(cx: &MatchCheckCtx<'_, 'p>, pat: &Pat) -> Self {
                     }
                 }
             }
-            &PatKind::LiteralBool { value } => {
-                ctor = IntRange(IntRange::from_bool(value));
+            // Constants evaluated to a `bool` may come as a plain literal.
+            PatKind::LiteralBool { value } | PatKind::Lit { value: Literal::Bool(value) } => {
+                ctor = IntRange(IntRange::from_bool(*value));
+                fields = Fields::empty();
+            }
+            // A `&str` literal is a reference to a `str` constant, so that it can be matched
+            // against other `&str` patterns.
+            PatKind::Lit { value: Literal::String(value) } => {
+                let str_ty = match pat.ty.as_reference() {
+                    Some((ty, ..)) => ty.clone(),
+                    None => TyKind::Str.intern(Interner),
+                };
+                ctor = Single;
+                let value = Interned::new_str(value);
+                fields = Fields::singleton(
+                    cx,
+                    DeconstructedPat::new(Str(value), Fields::empty(), str_ty),
+                );
+            }
+            PatKind::Lit { value } => {
+                ctor = int_range_ctor(pat, value, value);
+                fields = Fields::empty();
+            }
+            PatKind::Range { lo, hi } => {
+                ctor = int_range_ctor(pat, lo, hi);
+                fields = Fields::empty();
+            }
+            PatKind::Slice { prefix, slice, suffix } => {
+                let array_len = match pat.ty.kind(Interner) {
+                    TyKind::Array(_, len) => try_usize_const(len).map(|len| len as usize),
+                    _ => None,
+                };
+                let kind = if slice.is_some() {
+                    VarLen(prefix.len(), suffix.len())
+                } else {
+                    FixedLen(prefix.len() + suffix.len())
+                };
+                ctor = Slice(Slice::new(array_len, kind));
+                fields = Fields::from_iter(cx, prefix.iter().chain(suffix).map(mkpat));
+            }
+            PatKind::Opaque => {
+                ctor = Opaque;
                 fields = Fields::empty();
             }
             PatKind::Or { .. } => {
@@ -980,7 +1637,19 @@ pub(crate) fn from_pat(cx: &MatchCheckCtx<'_, 'p>, pat: &Pat) -> Self {
     }
 
     pub(crate) fn to_pat(&self, cx: &MatchCheckCtx<'_, 'p>) -> Pat {
-        let mut subpatterns = self.iter_fields().map(|p| p.to_pat(cx));
+        self.to_pat_(cx, false)
+    }
+
+    /// Like [`Self::to_pat`], but wildcards are replaced by an example value where one is cheaply
+    /// available: `0` for integers, `false` for `bool` and the first variant for enums. A
+    /// wildcard in a witness stands for any value, so the example is a witness too.
+    #[cfg(test)]
+    pub(crate) fn to_example_pat(&self, cx: &MatchCheckCtx<'_, 'p>) -> Pat {
+        self.to_pat_(cx, true)
+    }
+
+    fn to_pat_(&self, cx: &MatchCheckCtx<'_, 'p>, example: bool) -> Pat {
+        let mut subpatterns = self.iter_fields().map(|p| p.to_pat_(cx, example));
         let pat = match &self.ctor {
             Single | Variant(_) => match self.ty.kind(Interner) {
                 TyKind::Tuple(..) => PatKind::Leaf {
@@ -1011,20 +1680,53 @@ pub(crate) fn to_pat(&self, cx: &MatchCheckCtx<'_, 'p>) -> Pat {
                         PatKind::Leaf { subpatterns }
                     }
                 }
-                // Note: given the expansion of `&str` patterns done in `expand_pattern`, we should
-                // be careful to reconstruct the correct constant pattern here. However a string
-                // literal pattern will never be reported as a non-exhaustiveness witness, so we
-                // ignore this issue.
-                TyKind::Ref(..) => PatKind::Deref { subpattern: subpatterns.next().unwrap() },
+                // A `&str` literal was expanded to a reference to a `str` constant in `from_pat`,
+                // fold it back so that it reads like the original pattern.
+                TyKind::Ref(..) => {
+                    let subpattern = subpatterns.next().unwrap();
+                    match &*subpattern.kind {
+                        PatKind::Lit { value: Literal::String(_) } => *subpattern.kind,
+                        _ => PatKind::Deref { subpattern },
+                    }
+                }
                 _ => {
                     never!("unexpected ctor for type {:?} {:?}", self.ctor, self.ty);
                     PatKind::Wild
                 }
             },
-            &Slice(slice) => match slice._unimplemented {},
-            &Str(void) => match void {},
+            Slice(slice) => match slice.kind {
+                FixedLen(_) => {
+                    PatKind::Slice { prefix: subpatterns.collect(), slice: None, suffix: vec![] }
+                }
+                VarLen(prefix, _) => {
+                    let is_wild = |p: &Pat| matches!(*p.kind, PatKind::Wild);
+                    let mut subpatterns = subpatterns.peekable();
+                    let mut prefix: Vec<_> = subpatterns.by_ref().take(prefix).collect();
+                    if slice.array_len.is_some() {
+                        // Improves diagnostics a bit: if the type is a known-size array, instead
+                        // of reporting `[x, _, .., _, y]`, we prefer to report `[x, .., y]`.
+                        // This is incorrect if the size is not known, since `[_, ..]` captures
+                        // arrays of lengths `>= 1` whereas `[..]` captures any length.
+                        while prefix.last().map_or(false, is_wild) {
+                            prefix.pop();
+                        }
+                        while subpatterns.peek().map_or(false, is_wild) {
+                            subpatterns.next();
+                        }
+                    }
+                    let suffix: Vec<_> = subpatterns.collect();
+                    let elem_ty = match self.ty.kind(Interner) {
+                        TyKind::Slice(elem_ty) | TyKind::Array(elem_ty, _) => elem_ty.clone(),
+                        _ => self.ty.clone(),
+                    };
+                    let wild = Pat { ty: elem_ty, kind: Box::new(PatKind::Wild) };
+                    PatKind::Slice { prefix, slice: Some(wild), suffix }
+                }
+            },
+            Str(value) => PatKind::Lit { value: Literal::String(Box::from(&**value)) },
             &FloatRange(void) => match void {},
             IntRange(range) => return range.to_pat(cx, self.ty.clone()),
+            Wildcard if example => example_value(cx, &self.ty),
             Wildcard | NonExhaustive => PatKind::Wild,
             Missing { .. } => {
                 never!(
@@ -1045,6 +1747,20 @@ pub(super) fn is_or_pat(&self) -> bool {
         matches!(self.ctor, Or)
     }
 
+    /// Whether this pattern is or contains an or-pattern.
+    pub(super) fn contains_or_pat(&self) -> bool {
+        self.is_or_pat() || self.iter_fields().any(DeconstructedPat::contains_or_pat)
+    }
+
+    /// Whether some value matches this pattern, assuming it contains no or-patterns. This is
+    /// what computing usefulness against an empty matrix amounts to, outside of the top level.
+    pub(super) fn is_inhabited(&self, cx: &MatchCheckCtx<'_, 'p>) -> bool {
+        match self.ctor {
+            Wildcard => !cx.is_uninhabited(&self.ty),
+            _ => self.iter_fields().all(|pat| pat.is_inhabited(cx)),
+        }
+    }
+
     pub(super) fn ctor(&self) -> &Constructor {
         &self.ctor
     }
@@ -1053,6 +1769,64 @@ pub(super) fn ty(&self) -> &Ty {
         &self.ty
     }
 
+    /// Collects the ranges of values of the integer type `scalar_ty` that this pattern matches at
+    /// the top level. `_` and bindings match all of them.
+    #[cfg(test)]
+    pub(super) fn collect_int_ranges(&self, scalar_ty: Scalar, ranges: &mut Vec<IntRange>) {
+        match &self.ctor {
+            IntRange(range) => ranges.push(range.clone()),
+            Wildcard => {
+                ranges.push(IntRange { range: 0..=IntRange::truncate(u128::MAX, scalar_ty) })
+            }
+            Or => self.iter_fields().for_each(|pat| pat.collect_int_ranges(scalar_ty, ranges)),
+            _ => {}
+        }
+    }
+
+    /// Returns whether no value matches both `self` and `other`, two patterns of the same type.
+    /// Constructors we can't compare, like opaque constants, are assumed to overlap.
+    #[cfg(test)]
+    pub(super) fn is_disjoint_from(&self, cx: &MatchCheckCtx<'_, 'p>, other: &Self) -> bool {
+        let fields_disjoint =
+            |self_fields: SmallVec<[&'p DeconstructedPat<'p>; 2]>,
+             other_fields: SmallVec<[&'p DeconstructedPat<'p>; 2]>| {
+                self_fields.iter().zip(other_fields.iter()).any(|(a, b)| a.is_disjoint_from(cx, b))
+            };
+        match (&self.ctor, &other.ctor) {
+            (Or, _) => self.iter_fields().all(|alt| alt.is_disjoint_from(cx, other)),
+            (_, Or) => other.iter_fields().all(|alt| self.is_disjoint_from(cx, alt)),
+            (Wildcard, _) | (_, Wildcard) => false,
+            (Variant(self_id), Variant(other_id)) if self_id != other_id => true,
+            (Single, Single) | (Variant(_), Variant(_)) => {
+                fields_disjoint(self.iter_fields().collect(), other.iter_fields().collect())
+            }
+            (IntRange(self_range), IntRange(other_range)) => {
+                self_range.intersection(other_range).is_none()
+            }
+            (Slice(self_slice), Slice(other_slice)) => match (self_slice.kind, other_slice.kind) {
+                (FixedLen(self_len), FixedLen(other_len)) if self_len != other_len => true,
+                (FixedLen(_), FixedLen(_)) => {
+                    fields_disjoint(self.iter_fields().collect(), other.iter_fields().collect())
+                }
+                // Align the variable-length pattern with the fixed-length one.
+                (FixedLen(len), VarLen(..)) | (VarLen(..), FixedLen(len)) => {
+                    let (fixed, var, var_slice) = match self_slice.kind {
+                        FixedLen(_) => (self, other, other_slice),
+                        VarLen(..) => (other, self, self_slice),
+                    };
+                    if !var_slice.kind.covers_length(len) {
+                        return true;
+                    }
+                    fields_disjoint(fixed.iter_fields().collect(), var.specialize(cx, &fixed.ctor))
+                }
+                // Both match all the long enough slices, whose middle elements are matched by
+                // different subpatterns depending on the length.
+                (VarLen(..), VarLen(..)) => false,
+            },
+            _ => false,
+        }
+    }
+
     pub(super) fn iter_fields<'a>(&'a self) -> impl Iterator<Item = &'p DeconstructedPat<'p>> + 'a {
         self.fields.iter_patterns()
     }
@@ -1072,7 +1846,34 @@ pub(super) fn specialize<'a>(
             (Slice(self_slice), Slice(other_slice))
                 if self_slice.arity() != other_slice.arity() =>
             {
-                match self_slice._unimplemented {}
+                // The only tricky case: two slices of different arity. Since `self_slice` covers
+                // `other_slice`, `self_slice` must be `VarLen`, i.e. of the form
+                // `[prefix, .., suffix]`. Moreover `other_slice` is guaranteed to have a larger
+                // arity. So we fill the middle part with enough wildcards to reach the length of
+                // the new, larger slice.
+                match self_slice.kind {
+                    FixedLen(_) => {
+                        never!("{:?} doesn't cover {:?}", self_slice, other_slice);
+                        self.fields.iter_patterns().collect()
+                    }
+                    VarLen(prefix, suffix) => {
+                        let elem_ty = match self.ty.kind(Interner) {
+                            TyKind::Slice(elem_ty) | TyKind::Array(elem_ty, _) => elem_ty.clone(),
+                            _ => {
+                                never!("bad slice pattern {:?} {:?}", self.ctor, self.ty);
+                                return self.fields.iter_patterns().collect();
+                            }
+                        };
+                        let fields = self.fields.fields;
+                        let prefix = &fields[..prefix];
+                        let suffix = &fields[self_slice.arity() - suffix..];
+                        let wildcard: &_ =
+                            cx.pattern_arena.alloc(DeconstructedPat::wildcard(elem_ty));
+                        let extra_wildcards = other_slice.arity() - self_slice.arity();
+                        let extra_wildcards = (0..extra_wildcards).map(|_| wildcard);
+                        prefix.iter().chain(extra_wildcards).chain(suffix).collect()
+                    }
+                }
             }
             _ => self.fields.iter_patterns().collect(),
         }
@@ -1083,9 +1884,34 @@ pub(super) fn specialize<'a>(
     pub(super) fn set_reachable(&self) {
         self.reachable.set(true)
     }
+    /// Marks this pattern and all of its subpatterns as reachable.
+    pub(super) fn set_reachable_recursively(&self) {
+        self.set_reachable();
+        self.iter_fields().for_each(DeconstructedPat::set_reachable_recursively);
+    }
     pub(super) fn is_reachable(&self) -> bool {
         self.reachable.get()
     }
+
+    /// Report the subpatterns that were not reachable, if any.
+    #[cfg(test)]
+    pub(super) fn unreachable_subpatterns(&'p self) -> Vec<&'p DeconstructedPat<'p>> {
+        let mut subpats = Vec::new();
+        self.collect_unreachable_subpatterns(&mut subpats);
+        subpats
+    }
+
+    #[cfg(test)]
+    fn collect_unreachable_subpatterns(&'p self, subpats: &mut Vec<&'p DeconstructedPat<'p>>) {
+        // We don't look at subpatterns if we already reported the whole pattern as unreachable.
+        if !self.is_reachable() {
+            subpats.push(self);
+        } else {
+            for p in self.iter_fields() {
+                p.collect_unreachable_subpatterns(subpats);
+            }
+        }
+    }
 }
 
 fn is_field_list_non_exhaustive(variant_id: VariantId, cx: &MatchCheckCtx<'_, '_>) -> bool {
@@ -1096,3 +1922,27 @@ fn is_field_list_non_exhaustive(variant_id: VariantId, cx: &MatchCheckCtx<'_, '_
     };
     cx.db.attrs(attr_def_id).by_key("non_exhaustive").exists()
 }
+
+/// Shows the constructors of the pattern and of its fields, like `Variant(..)(_, IntRange(..))`,
+/// to trace the algorithm. Patterns are shown to users with `to_pat` and `HirDisplay` instead.
+#[cfg(feature = "trace")]
+impl std::fmt::Debug for DeconstructedPat<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.ctor {
+            Wildcard => write!(f, "_")?,
+            ctor => write!(f, "{:?}", ctor)?,
+        }
+        let mut fields = self.iter_fields().peekable();
+        if fields.peek().is_some() {
+            write!(f, "(")?;
+            for (idx, field) in fields.enumerate() {
+                if idx != 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{:?}", field)?;
+            }
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
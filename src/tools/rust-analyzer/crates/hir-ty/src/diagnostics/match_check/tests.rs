@@ -0,0 +1,2710 @@
+use base_db::fixture::WithFixture;
+use chalk_ir::{InferenceVar, Scalar, TyVariableKind};
+use hir_def::{
+    body::Body,
+    db::DefDatabase,
+    expr::{Expr, ExprId, Literal, Statement},
+    DefWithBodyId, EnumVariantId, HasModule, ModuleDefId,
+};
+use hir_expand::name::Name;
+use smallvec::smallvec;
+use test_utils::{bench, skip_slow_tests};
+use typed_arena::Arena;
+
+use crate::{
+    db::HirDatabase, diagnostics::expr::uncovered_patterns, display::HirDisplay, test_db::TestDB,
+    Interner, Ty, TyBuilder, TyKind,
+};
+
+use super::{
+    arms_disjoint, check_irrefutable, check_let_chain, check_matches_macro,
+    deconstruct_pat::{Constructor, DeconstructedPat, Fields, IntRange, SplitWildcard},
+    usefulness::{
+        self, arm_coverage_counts, check_exhaustive, compute_match_usefulness, coverage_diff,
+        dedup_witnesses, exhaustive_if_added, exhaustive_without, int_coverage,
+        int_coverage_fraction, non_exhaustive_witness_strings, range_overlap_map, render_witnesses,
+        InfiniteType, InvalidRow, MatchCheckCtx, Matrix, NonExhaustiveReason, PatStack,
+        PatWitnessSink, Reachability, UsefulnessReport, Witness, WitnessKey, WitnessSink,
+    },
+    MatchArm, MatchesOutcome, OrPatternSimplifier, Pat, PatCtxt, PatKind, PatNameCache,
+    PatternContext, PatternFoldable,
+};
+
+/// Returns the function named `main` in the first file of the fixture.
+fn main_fn(ra_fixture: &str) -> (TestDB, DefWithBodyId) {
+    let (db, file_ids) = TestDB::with_many_files(ra_fixture);
+    let file_id = file_ids[0];
+    let module_id = db.module_for_file(file_id);
+    let def_map = module_id.def_map(&db);
+    let scope = &def_map[module_id.local_id].scope;
+    let func = scope
+        .declarations()
+        .find_map(|x| match x {
+            ModuleDefId::FunctionId(x) if db.function_data(x).name.to_string() == "main" => Some(x),
+            _ => None,
+        })
+        .unwrap();
+    (db, func.into())
+}
+
+/// Passes the match check context of the function named `main` to `f`.
+fn with_main_cx<R>(ra_fixture: &str, f: impl FnOnce(&MatchCheckCtx<'_, '_>) -> R) -> R {
+    with_main_cx_configured(ra_fixture, |_| (), f)
+}
+
+/// Like `with_main_cx`, but lets `configure` change the context first.
+fn with_main_cx_configured<R>(
+    ra_fixture: &str,
+    configure: impl FnOnce(&mut MatchCheckCtx<'_, '_>),
+    f: impl FnOnce(&MatchCheckCtx<'_, '_>) -> R,
+) -> R {
+    let (db, owner) = main_fn(ra_fixture);
+    let arena = Arena::new();
+    let mut cx = MatchCheckCtx::new(owner.module(&db), owner, &db, &arena);
+    configure(&mut cx);
+    f(&cx)
+}
+
+/// Computes the usefulness report of the first `match` of the function named `main`.
+fn check_match<R>(
+    ra_fixture: &str,
+    f: impl FnOnce(&MatchCheckCtx<'_, '_>, &UsefulnessReport<'_>) -> R,
+) -> R {
+    check_match_with(ra_fixture, |_| (), f)
+}
+
+/// Like `check_match`, but lets `configure` change the context the match is checked in.
+fn check_match_with<R>(
+    ra_fixture: &str,
+    configure: impl FnOnce(&mut MatchCheckCtx<'_, '_>),
+    f: impl FnOnce(&MatchCheckCtx<'_, '_>, &UsefulnessReport<'_>) -> R,
+) -> R {
+    with_main_cx_configured(ra_fixture, configure, |cx| {
+        let (scrut_ty, arms) = lower_first_match(cx);
+        f(cx, &compute_match_usefulness(cx, &arms, &scrut_ty))
+    })
+}
+
+/// Renders the witnesses of non-exhaustiveness of `report` like diagnostics show them.
+fn rendered_witnesses(cx: &MatchCheckCtx<'_, '_>, report: &UsefulnessReport<'_>) -> Vec<String> {
+    rendered_pats(cx, &report.non_exhaustiveness_witnesses)
+}
+
+/// Renders deconstructed patterns, like witnesses or arms, as patterns.
+fn rendered_pats(cx: &MatchCheckCtx<'_, '_>, pats: &[DeconstructedPat<'_>]) -> Vec<String> {
+    pats.iter().map(|pat| pat.to_pat(cx).display(cx.db).to_string()).collect()
+}
+
+/// Lowers the arms of the first `match` of the body checked by `cx`, and returns them with the type
+/// of the scrutinee.
+fn lower_first_match<'p>(cx: &MatchCheckCtx<'_, 'p>) -> (Ty, Vec<MatchArm<'p>>) {
+    let body = cx.db.body(cx.body);
+    let infer = cx.db.infer(cx.body);
+    let (scrutinee, arms) = first_match(&body);
+
+    let mut patcx = PatCtxt::new(cx.db, cx.body, &infer, &body);
+    let arms: Vec<_> = arms
+        .iter()
+        .map(|arm| {
+            let pat = patcx.lower_pattern(arm.pat);
+            let pat = cx.pattern_arena.alloc(DeconstructedPat::from_pat(cx, &pat));
+            MatchArm { pat, has_guard: arm.guard.is_some() }
+        })
+        .collect();
+    assert!(patcx.errors.is_empty(), "{:?}", patcx.errors);
+    (infer[scrutinee].clone(), arms)
+}
+
+/// Returns the scrutinee and the arms of the first `match` of `body`.
+fn first_match(body: &Body) -> (ExprId, &[hir_def::expr::MatchArm]) {
+    body.exprs
+        .iter()
+        .find_map(|(_, expr)| match expr {
+            Expr::Match { expr, arms } => Some((*expr, &**arms)),
+            _ => None,
+        })
+        .unwrap()
+}
+
+/// Lowers the arm patterns of the first `match` of the function named `main` and passes their
+/// constructors to `f`, with `None` for the patterns that fail to lower.
+fn check_arm_ctors(
+    ra_fixture: &str,
+    f: impl FnOnce(&dyn HirDatabase, &Ty, &[Option<Constructor>]),
+) {
+    with_main_cx(ra_fixture, |cx| {
+        let body = cx.db.body(cx.body);
+        let infer = cx.db.infer(cx.body);
+        let (scrutinee, arms) = first_match(&body);
+        let ctors: Vec<_> = arms
+            .iter()
+            .map(|arm| {
+                let mut patcx = PatCtxt::new(cx.db, cx.body, &infer, &body);
+                let pat = patcx.lower_pattern(arm.pat);
+                let ctor = DeconstructedPat::from_pat(cx, &pat).ctor().clone();
+                patcx.errors.is_empty().then(|| ctor)
+            })
+            .collect();
+        f(cx.db, &infer[scrutinee], &ctors);
+    })
+}
+
+#[test]
+fn classify_enum_patterns() {
+    check_arm_ctors(
+        r#"
+enum Opt { Some(i32), None }
+fn main(x: Opt) {
+    match x {
+        Opt::Some(_) => {}
+        Opt::None | Opt::Some(1) => {}
+        ref y @ Opt::None => {}
+        y => {}
+    }
+}
+"#,
+        |db, _, ctors| {
+            let ctors: Vec<_> = ctors.iter().map(|ctor| ctor.clone().unwrap()).collect();
+            let (some, bound) = (ctors[0].as_variant().unwrap(), ctors[2].as_variant().unwrap());
+            assert_ne!(some, bound);
+            assert_eq!(some.parent, bound.parent);
+            let variant_name =
+                |id: EnumVariantId| db.enum_data(id.parent).variants[id.local_id].name.to_string();
+            assert_eq!(variant_name(some), "Some");
+            assert_eq!(variant_name(bound), "None");
+            assert_eq!(ctors[1], Constructor::Or);
+            assert_eq!(ctors[3], Constructor::Wildcard);
+        },
+    );
+}
+
+#[test]
+fn classify_struct_patterns() {
+    check_arm_ctors(
+        r#"
+struct S { a: u8 }
+fn main(x: S) {
+    match x {
+        S { a: 0 } => {}
+        S { .. } => {}
+    }
+}
+"#,
+        |_, _, ctors| {
+            for ctor in ctors {
+                let ctor = ctor.as_ref().unwrap();
+                assert_eq!(ctor, &Constructor::Single);
+                assert_eq!(ctor.as_variant(), None);
+            }
+        },
+    );
+}
+
+#[test]
+fn classify_int_patterns() {
+    check_arm_ctors(
+        r#"
+fn main(x: i8) {
+    match x {
+        -5..=10 => {}
+        3 => {}
+        -128..0 => {}
+        _ => {}
+    }
+}
+"#,
+        |_, ty, ctors| {
+            let range = |lo, hi| {
+                let (lo, hi) = (Literal::Int(lo, None), Literal::Int(hi, None));
+                Some(Constructor::IntRange(IntRange::from_literal_range(&lo, &hi, ty).unwrap()))
+            };
+            assert_eq!(
+                ctors,
+                [range(-5, 10), range(3, 3), range(-128, -1), Some(Constructor::Wildcard)]
+            );
+        },
+    );
+}
+
+#[test]
+fn inline_const_patterns() {
+    check_arm_ctors(
+        r#"
+fn unknown() -> u8 { 0 }
+fn main(x: u8) {
+    match x {
+        const { 2 + 3 } => {}
+        const { unknown() } => {}
+        _ => {}
+    }
+}
+"#,
+        |_, ty, ctors| {
+            let five = IntRange::from_literal(&Literal::Uint(5, None), ty).unwrap();
+            assert_eq!(
+                ctors,
+                [
+                    Some(Constructor::IntRange(five)),
+                    Some(Constructor::Opaque),
+                    Some(Constructor::Wildcard)
+                ]
+            );
+        },
+    );
+    check_match(
+        r#"
+fn main(x: u8) {
+    match x {
+        const { 2 + 3 } => {}
+        0..=4 | 6..=255 => {}
+    }
+}
+"#,
+        |_, report| {
+            assert!(report.non_exhaustiveness_witnesses.is_empty());
+        },
+    );
+}
+
+#[test]
+fn disjoint_arms() {
+    with_main_cx(
+        r#"
+//- minicore: option
+fn main(x: Option<i32>) {
+    match x {
+        Some(0) => {}
+        Some(1) => {}
+        Some(_) => {}
+        None => {}
+    }
+}
+"#,
+        |cx| {
+            let body = cx.db.body(cx.body);
+            let infer = cx.db.infer(cx.body);
+            let (scrutinee, arms) = first_match(&body);
+            let disjoint =
+                |a: usize, b: usize| arms_disjoint(cx, arms[a].pat, arms[b].pat, &infer[scrutinee]);
+            assert!(disjoint(0, 1));
+            assert!(!disjoint(0, 2));
+            assert!(disjoint(3, 2));
+            assert!(!disjoint(2, 2));
+        },
+    );
+}
+
+#[test]
+fn guarded_arm_with_unreachable_pattern() {
+    check_match(
+        r#"
+enum Opt { Some(bool), None }
+fn main(x: Opt, cond: bool) {
+    match x {
+        Opt::Some(_) => {}
+        Opt::Some(true) if cond => {}
+        Opt::None if cond => {}
+        _ => {}
+    }
+}
+"#,
+        |_, report| assert_eq!(report.guard_unreachable(), [1]),
+    );
+}
+
+#[test]
+fn guarded_binding_arm() {
+    check_match(
+        r#"
+fn main(x: i32) {
+    match x {
+        n if n > 5 => {}
+        _ => {}
+    }
+}
+"#,
+        |_, report| {
+            assert!(report
+                ._arm_usefulness
+                .iter()
+                .all(|(_, reachability)| matches!(reachability, Reachability::Reachable)));
+            assert!(report.guard_unreachable().is_empty());
+            assert!(report.non_exhaustiveness_witnesses.is_empty());
+        },
+    );
+    check_match(
+        r#"
+fn main(x: i32) {
+    match x {
+        n if n > 5 => {}
+    }
+}
+"#,
+        |_, report| {
+            assert!(matches!(report._arm_usefulness[0].1, Reachability::Reachable));
+            assert!(!report.non_exhaustiveness_witnesses.is_empty());
+        },
+    );
+}
+
+#[test]
+fn unreachable_or_pattern_alternatives() {
+    check_match(
+        r#"
+enum Opt { Some(i32), None }
+fn main(x: Opt) {
+    match x {
+        Opt::None => {}
+        Opt::Some(0) | Opt::None => {}
+        Opt::None | Opt::Some(1) => {}
+        Opt::Some(2 | 0) => {}
+        _ => {}
+    }
+}
+"#,
+        |cx, report| {
+            let unreachable: Vec<Vec<_>> = report
+                ._arm_usefulness
+                .iter()
+                .map(|(arm, reachability)| {
+                    assert!(reachability.is_reachable(), "every arm is reachable");
+                    let subpats = arm.unreachable_subpatterns(cx);
+                    subpats.iter().map(|pat| pat.display(cx.db).to_string()).collect()
+                })
+                .collect();
+            assert_eq!(unreachable, [vec![], vec!["None"], vec!["None"], vec!["0"], vec![]]);
+        },
+    );
+}
+
+#[test]
+fn unit_scrutinee() {
+    check_match(
+        r#"
+fn main() {
+    match () {
+        () => {}
+    }
+}
+"#,
+        |_, report| {
+            let pat = report._arm_usefulness[0].0.pat;
+            assert_eq!(pat.ctor(), &Constructor::Single);
+            assert_eq!(pat.iter_fields().count(), 0);
+            assert!(report.non_exhaustiveness_witnesses.is_empty());
+        },
+    );
+}
+
+#[test]
+fn tuple_arities() {
+    check_match(
+        r#"
+fn main(x: (i32,)) {
+    match x {
+        (0,) => {}
+        (_,) => {}
+    }
+}
+"#,
+        |_, report| {
+            for (arm, _) in &report._arm_usefulness {
+                assert_eq!(arm.pat.ctor(), &Constructor::Single);
+                assert_eq!(arm.pat.iter_fields().count(), 1);
+            }
+            assert!(report._arm_usefulness.iter().all(|(_, it)| it.is_reachable()));
+            assert!(report.non_exhaustiveness_witnesses.is_empty());
+        },
+    );
+    // Parentheses around a pattern don't make a tuple.
+    check_match(
+        r#"
+fn main(x: i32) {
+    match x {
+        (0) => {}
+        (_) => {}
+    }
+}
+"#,
+        |_, report| {
+            let arms = &report._arm_usefulness;
+            assert!(matches!(arms[0].0.pat.ctor(), Constructor::IntRange(_)));
+            assert!(arms[1].0.pat.ctor().is_wildcard());
+            assert!(report.non_exhaustiveness_witnesses.is_empty());
+        },
+    );
+    check_match(
+        r#"
+fn main(x: (bool, bool)) {
+    match x {
+        (true, _) => {}
+    }
+}
+"#,
+        |cx, report| {
+            assert_eq!(report._arm_usefulness[0].0.pat.iter_fields().count(), 2);
+            assert_eq!(rendered_witnesses(cx, report), ["(false, _)"]);
+        },
+    );
+}
+
+#[test]
+fn single_inhabitant_witnesses() {
+    let check = |ra_fixture: &str, expect: &[&str]| {
+        check_match_with(
+            ra_fixture,
+            |cx| cx.collect_stats(),
+            |cx, report| {
+                let witnesses = rendered_witnesses(cx, report);
+                assert_eq!(witnesses, expect);
+                // The witness is found without splitting the type into constructors.
+                assert!(cx.take_stats().unwrap().splits.is_empty());
+
+                let ty = report.non_exhaustiveness_witnesses[0].ty().clone();
+                let general = usefulness::compute_witnesses(cx, &Matrix::empty(), ty);
+                assert_eq!(rendered_pats(cx, &general), witnesses);
+                assert!(!cx.take_stats().unwrap().splits.is_empty());
+            },
+        )
+    };
+    check(
+        r#"
+struct UnitStruct;
+fn main(u: UnitStruct) {
+    match u {}
+}
+"#,
+        &["UnitStruct"],
+    );
+    check(
+        r#"
+struct UnitStruct;
+fn main(u: ((), UnitStruct)) {
+    match u {}
+}
+"#,
+        &["(_, _)"],
+    );
+    check_match(
+        r#"
+struct UnitStruct;
+fn main(u: UnitStruct) {
+    match u {
+        UnitStruct => {}
+    }
+}
+"#,
+        |_, report| assert!(report.non_exhaustiveness_witnesses.is_empty()),
+    );
+}
+
+#[test]
+fn self_recursive_struct_scrutinee() {
+    for features in ["", "#![feature(exhaustive_patterns)]"] {
+        check_match(
+            &format!(
+                r#"
+{features}
+struct A(A);
+struct B(C);
+struct C(B);
+fn main(a: A, b: B) {{
+    match (a, b) {{}}
+}}
+"#
+            ),
+            |cx, report| {
+                assert_eq!(rendered_witnesses(cx, report), ["(_, _)"]);
+            },
+        );
+    }
+}
+
+#[test]
+fn no_rows_shortcut_agrees_with_splitting() {
+    for (features, expect) in [
+        ("", [true, true, true, true]),
+        ("#![feature(exhaustive_patterns)]", [true, false, false, false]),
+    ] {
+        check_match(
+            &format!(
+                r#"
+//- minicore: option
+{features}
+enum Void {{}}
+struct S {{ x: u8, v: Void }}
+fn unknown() -> u8 {{ 0 }}
+fn main(x: (u8, Option<Void>, Option<S>)) {{
+    match x {{
+        (const {{ unknown() }}, None, None) => {{}}
+        (_, Some(_), _) => {{}}
+        (0, _, Some(S {{ x: 1, .. }})) => {{}}
+        (_, None, Some(_)) => {{}}
+    }}
+}}
+"#
+            ),
+            |cx, report| {
+                let (shortcut, general): (Vec<_>, Vec<_>) = report
+                    ._arm_usefulness
+                    .iter()
+                    .map(|(arm, _)| usefulness::usefulness_against_no_rows(cx, arm.pat))
+                    .unzip();
+                assert_eq!(shortcut, general, "{}", features);
+                assert_eq!(shortcut, expect, "{}", features);
+            },
+        );
+    }
+}
+
+#[test]
+fn benchmark_wildcard_heavy_match() {
+    if skip_slow_tests() {
+        return;
+    }
+    let columns = 24;
+    let tys = vec!["Option<bool>"; columns].join(", ");
+    let arms: String = (0..columns)
+        .map(|col| {
+            let mut pats = vec!["_"; columns];
+            pats[col] = "Some(true)";
+            format!("({}) => {{}}\n", pats.join(", "))
+        })
+        .collect();
+    let ra_fixture =
+        format!("//- minicore: option\nfn main(x: ({tys})) {{ match x {{ {arms} _ => {{}} }} }}");
+
+    with_main_cx(&ra_fixture, |cx| {
+        let (scrut_ty, arms) = lower_first_match(cx);
+        let report = {
+            let _b = bench("wildcard-heavy match");
+            compute_match_usefulness(cx, &arms, &scrut_ty)
+        };
+        assert!(report._arm_usefulness.iter().all(|(_, reachability)| reachability.is_reachable()));
+        assert!(report.non_exhaustiveness_witnesses.is_empty());
+    });
+}
+
+#[test]
+fn reachability_accessors() {
+    check_match(
+        r#"
+//- minicore: option
+fn main(x: Option<i32>) {
+    match x {
+        Some(_) | None => {}
+        None => {}
+        Some(0) | Some(_) => {}
+    }
+}
+"#,
+        |cx, report| {
+            let accessors: Vec<_> = report
+                ._arm_usefulness
+                .iter()
+                .map(|(arm, reachability)| {
+                    let subpats: Vec<_> = arm
+                        .unreachable_subpatterns(cx)
+                        .iter()
+                        .map(|pat| pat.display(cx.db).to_string())
+                        .collect();
+                    (reachability.is_reachable(), subpats)
+                })
+                .collect();
+            assert_eq!(accessors, [(true, vec![]), (false, vec![]), (false, vec![])]);
+        },
+    );
+    check_match(
+        r#"
+//- minicore: option
+fn main(x: Option<i32>) {
+    match x {
+        None => {}
+        Some(_) | None => {}
+    }
+}
+"#,
+        |cx, report| {
+            let (arm, reachability) = &report._arm_usefulness[1];
+            assert!(reachability.is_reachable());
+            let subpats: Vec<_> = arm
+                .unreachable_subpatterns(cx)
+                .iter()
+                .map(|pat| pat.display(cx.db).to_string())
+                .collect();
+            assert_eq!(subpats, ["None"]);
+        },
+    );
+}
+
+#[test]
+fn arm_covered_by_or_pattern_arm() {
+    let reachable = |ra_fixture: &str| {
+        let mut reachable = Vec::new();
+        check_match(ra_fixture, |_, report| {
+            reachable = report
+                ._arm_usefulness
+                .iter()
+                .map(|(_, reachability)| reachability.is_reachable())
+                .collect();
+        });
+        reachable
+    };
+    assert_eq!(
+        reachable(
+            r#"
+//- minicore: option
+fn main(x: Option<i32>) {
+    match x {
+        Some(0) | Some(1) => {}
+        Some(0) => {}
+        Some(1) | Some(2) => {}
+        _ => {}
+    }
+}
+"#
+        ),
+        [true, false, true, true]
+    );
+    assert_eq!(
+        reachable(
+            r#"
+//- minicore: option
+fn main(x: Option<i32>) {
+    match x {
+        Some(0 | 1) => {}
+        Some(1) => {}
+        None | Some(2) => {}
+        None => {}
+        _ => {}
+    }
+}
+"#
+        ),
+        [true, false, true, false, true]
+    );
+}
+
+#[test]
+fn or_patterns_of_literals_and_ranges() {
+    check_match(
+        r#"
+fn main(x: u8) {
+    match x {
+        1 | 3 | 5..=10 => {}
+        0 | 2 | 4 | 11..=255 => {}
+    }
+}
+"#,
+        |_, report| assert!(report.non_exhaustiveness_witnesses.is_empty()),
+    );
+    check_match(
+        r#"
+fn main(x: u8) {
+    match x {
+        5..=10 | 5 => {}
+        20 | 20..=30 => {}
+        3 | 1..=4 | 2 => {}
+        _ => {}
+    }
+}
+"#,
+        |cx, report| {
+            let unreachable: Vec<Vec<_>> = report
+                ._arm_usefulness
+                .iter()
+                .map(|(arm, reachability)| {
+                    assert!(reachability.is_reachable(), "every arm is reachable");
+                    let subpats = arm.unreachable_subpatterns(cx);
+                    subpats.iter().map(|pat| pat.display(cx.db).to_string()).collect()
+                })
+                .collect();
+            // `20..=30` still matches `21..=30` after `20`.
+            assert_eq!(unreachable, [vec!["5"], vec![], vec!["2"], vec![]]);
+        },
+    );
+}
+
+#[test]
+fn byte_string_catch_all_is_reachable() {
+    check_match(
+        r#"
+fn main(b: &[u8; 2]) {
+    match b {
+        b"ab" => {}
+        _ => {}
+    }
+}
+"#,
+        |_, report| {
+            assert!(report
+                ._arm_usefulness
+                .iter()
+                .all(|(_, reachability)| matches!(reachability, Reachability::Reachable)));
+            assert!(report.non_exhaustiveness_witnesses.is_empty());
+        },
+    );
+}
+
+#[test]
+fn single_value_and_empty_ranges() {
+    check_match(
+        r#"
+fn main(x: u8) {
+    match x {
+        5..=5 => {}
+        5 => {}
+        6..6 => {}
+        _ => {}
+    }
+}
+"#,
+        |_, report| {
+            let unreachable: Vec<_> = report
+                ._arm_usefulness
+                .iter()
+                .map(|(_, reachability)| matches!(reachability, Reachability::Unreachable))
+                .collect();
+            assert_eq!(unreachable, [false, true, true, false]);
+        },
+    );
+}
+
+#[test]
+fn tuple_rest_patterns() {
+    check_match(
+        r#"
+fn main(t: (u8, u8, u8)) {
+    match t {
+        (0, ..) => {}
+        (.., 0) => {}
+        (1..=255, .., 1..=255) => {}
+    }
+}
+"#,
+        |cx, report| {
+            let arms: Vec<_> = report
+                ._arm_usefulness
+                .iter()
+                .map(|(arm, _)| arm.pat.to_pat(cx).display(cx.db).to_string())
+                .collect();
+            assert_eq!(arms, ["(0, _, _)", "(_, _, 0)", "(1..=255, _, 1..=255)"]);
+            assert!(report
+                ._arm_usefulness
+                .iter()
+                .all(|(_, reachability)| matches!(reachability, Reachability::Reachable)));
+            assert!(report.non_exhaustiveness_witnesses.is_empty());
+        },
+    );
+}
+
+#[test]
+fn pointer_sized_integers_with_known_width() {
+    let fixture = r#"
+fn main(x: usize) {
+    match x {
+        0..=18446744073709551615 => {}
+    }
+}
+"#;
+    check_match(fixture, |_, report| {
+        assert_eq!(report.non_exhaustiveness_witnesses.len(), 1);
+    });
+    check_match(&format!("//- /main.rs cfg:target_pointer_width=64{}", fixture), |_, report| {
+        assert!(report.non_exhaustiveness_witnesses.is_empty());
+    });
+
+    check_match(
+        r#"
+//- /main.rs cfg:target_pointer_width=32
+fn main(x: isize) {
+    match x {
+        -2147483648..=-1 => {}
+        1..=2147483647 => {}
+    }
+}
+"#,
+        |cx, report| {
+            assert_eq!(rendered_witnesses(cx, report), ["0"]);
+        },
+    );
+}
+
+#[test]
+fn simplify_or_patterns() {
+    let (db, owner) = main_fn(
+        r#"
+//- minicore: option
+fn main(x: Option<i32>) {
+    match x {
+        _ | Some(0) => {}
+        Some(1) | _ | None => {}
+    }
+}
+"#,
+    );
+    let body = db.body(owner);
+    let infer = db.infer(owner);
+    let (_, arms) = first_match(&body);
+
+    let mut patcx = PatCtxt::new(&db, owner, &infer, &body);
+    let mut simplifier = OrPatternSimplifier::default();
+    let simplified: Vec<_> = arms
+        .iter()
+        .map(|arm| patcx.lower_pattern(arm.pat).fold_with(&mut simplifier))
+        .map(|pat| pat.display(&db).to_string())
+        .collect();
+    assert!(patcx.errors.is_empty(), "{:?}", patcx.errors);
+    assert_eq!(simplified, ["_", "Some(1) | _"]);
+    let dropped: Vec<_> =
+        simplifier.dropped.iter().map(|pat| pat.display(&db).to_string()).collect();
+    assert_eq!(dropped, ["Some(0)", "None"]);
+}
+
+#[test]
+fn infallible_is_uninhabited() {
+    for (features, uninhabited) in [("", false), ("#![feature(exhaustive_patterns)]", true)] {
+        let ra_fixture = format!(
+            r#"
+//- minicore: infallible
+{features}
+fn main(x: core::convert::Infallible) {{}}
+"#
+        );
+        with_main_cx(&ra_fixture, |cx| {
+            let body = cx.db.body(cx.body);
+            let infer = cx.db.infer(cx.body);
+            let ty = &infer[body.params[0]];
+            assert_eq!(ty.display(cx.db).to_string(), "Infallible");
+            assert_eq!(cx.is_uninhabited(ty), uninhabited, "{}", features);
+        });
+    }
+}
+
+#[test]
+fn references_to_uninhabited_types_are_inhabited() {
+    with_main_cx(
+        r#"
+#![feature(exhaustive_patterns)]
+enum Never {}
+fn main(x: Never, y: &Never) {}
+"#,
+        |cx| {
+            let body = cx.db.body(cx.body);
+            let infer = cx.db.infer(cx.body);
+            assert!(cx.is_uninhabited(&infer[body.params[0]]));
+            assert!(!cx.is_uninhabited(&infer[body.params[1]]));
+        },
+    );
+
+    check_match("enum Never {} fn main(x: Never) { match x {} }", |_, report| {
+        assert!(report.non_exhaustiveness_witnesses.is_empty());
+    });
+    check_match("enum Never {} fn main(x: &Never) { match x {} }", |_, report| {
+        assert_eq!(report.non_exhaustiveness_witnesses.len(), 1);
+    });
+}
+
+#[test]
+fn matches_macro_outcome() {
+    let check = |main: &str, expect: MatchesOutcome| {
+        let ra_fixture = format!(
+            r#"
+//- minicore: option
+macro_rules! matches {{
+    ($expression:expr, $pattern:pat $(if $guard:expr)? $(,)?) => {{
+        match $expression {{
+            $pattern $(if $guard)? => true,
+            _ => false
+        }}
+    }};
+}}
+{main}
+"#
+        );
+        let outcome = with_main_cx(&ra_fixture, |cx| {
+            let body = cx.db.body(cx.body);
+            let infer = cx.db.infer(cx.body);
+            let (scrutinee, arms) = first_match(&body);
+            let arm = &arms[0];
+            check_matches_macro(cx, arm.pat, arm.guard.is_some(), &infer[scrutinee]).unwrap()
+        });
+        assert_eq!(outcome, expect, "{main}");
+    };
+
+    check("fn main(x: Option<i32>) { matches!(x, _); }", MatchesOutcome::AlwaysTrue);
+    check("fn main(x: Option<i32>) { matches!(x, Some(_)); }", MatchesOutcome::Depends);
+    check("fn main(x: Option<i32>, c: bool) { matches!(x, _ if c); }", MatchesOutcome::Depends);
+    check("fn main(x: ()) { matches!(x, Some(_)); }", MatchesOutcome::AlwaysFalse);
+    check("fn main(x: Option<bool>) { matches!(x, Some(true) | None); }", MatchesOutcome::Depends);
+    check(
+        "fn main(x: Option<bool>) { matches!(x, Some(true) | None | Some(false)); }",
+        MatchesOutcome::AlwaysTrue,
+    );
+}
+
+#[test]
+fn or_patterns_of_different_constructors() {
+    check_match(
+        r#"
+//- minicore: option
+fn main(x: Option<bool>) {
+    match x {
+        Some(true) | None => {}
+        Some(false) => {}
+    }
+}
+"#,
+        |cx, report| {
+            for (arm, reachability) in &report._arm_usefulness {
+                assert!(reachability.is_reachable());
+                assert!(arm.unreachable_subpatterns(cx).is_empty());
+            }
+            assert!(report.non_exhaustiveness_witnesses.is_empty());
+        },
+    );
+    check_match(
+        r#"
+//- minicore: option
+fn main(x: Option<bool>) {
+    match x {
+        Some(true) | None => {}
+        None | Some(false) => {}
+        Some(_) => {}
+    }
+}
+"#,
+        |cx, report| {
+            let arms = &report._arm_usefulness;
+            let unreachable: Vec<_> = arms[1]
+                .0
+                .unreachable_subpatterns(cx)
+                .iter()
+                .map(|pat| pat.display(cx.db).to_string())
+                .collect();
+            assert_eq!(unreachable, ["None"]);
+            assert!(!arms[2].1.is_reachable());
+            assert!(report.non_exhaustiveness_witnesses.is_empty());
+        },
+    );
+}
+
+#[test]
+fn duplicate_str_arms() {
+    let mut arms: Vec<_> = (0..49).map(|i| format!("\"arm{i}\" => {{}}")).collect();
+    arms.insert(30, "\"arm7\" => {}".to_owned());
+    let ra_fixture = format!("fn main(s: &str) {{ match s {{ {} _ => {{}} }} }}", arms.join(" "));
+    check_match(&ra_fixture, |cx, report| {
+        let arms = &report._arm_usefulness;
+        assert_eq!(arms.len(), 51);
+        let unreachable: Vec<_> = arms
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, reachability))| !reachability.is_reachable())
+            .map(|(idx, (arm, _))| (idx, arm.pat.to_pat(cx).display(cx.db).to_string()))
+            .collect();
+        assert_eq!(unreachable, [(30, "\"arm7\"".to_owned())]);
+        assert!(report.non_exhaustiveness_witnesses.is_empty());
+    });
+}
+
+#[test]
+fn or_patterns_binding_the_same_name() {
+    check_match(
+        r#"
+//- minicore: result
+fn main(r: Result<i32, i32>) {
+    match r {
+        Ok(x) | Err(x) => {}
+        Ok(_) => {}
+    }
+}
+"#,
+        |cx, report| {
+            let (arm, reachability) = &report._arm_usefulness[0];
+            assert!(reachability.is_reachable());
+            assert!(arm.unreachable_subpatterns(cx).is_empty());
+            // The bindings are lowered to wildcards in both alternatives alike.
+            assert!(arm.pat.is_or_pat());
+            for alt in arm.pat.iter_fields() {
+                assert!(matches!(alt.ctor(), Constructor::Variant(_)));
+                assert!(alt.iter_fields().all(|field| field.ctor().is_wildcard()));
+            }
+            assert!(!report._arm_usefulness[1].1.is_reachable());
+            assert!(report.non_exhaustiveness_witnesses.is_empty());
+        },
+    );
+}
+
+#[test]
+fn or_patterns_behind_references() {
+    check_match(
+        r#"
+fn main(b: &bool) {
+    match b {
+        &true | &false => {}
+    }
+}
+"#,
+        |cx, report| {
+            let (arm, reachability) = &report._arm_usefulness[0];
+            assert!(reachability.is_reachable());
+            assert!(arm.unreachable_subpatterns(cx).is_empty());
+            assert!(report.non_exhaustiveness_witnesses.is_empty());
+        },
+    );
+    check_match(
+        r#"
+fn main(b: &bool) {
+    match b {
+        &true | &true => {}
+    }
+}
+"#,
+        |cx, report| {
+            let (arm, reachability) = &report._arm_usefulness[0];
+            assert!(reachability.is_reachable());
+            let unreachable: Vec<_> = arm
+                .unreachable_subpatterns(cx)
+                .iter()
+                .map(|pat| pat.display(cx.db).to_string())
+                .collect();
+            assert_eq!(unreachable, ["&true"]);
+            assert_eq!(rendered_witnesses(cx, report), ["&false"]);
+        },
+    );
+}
+
+#[test]
+fn merge_reports_of_split_matches() {
+    with_main_cx(
+        r#"
+//- minicore: option
+fn main(x: Option<bool>) {
+    match x {
+        Some(true) => {}
+        None if true => {}
+    }
+    match x {
+        None => {}
+        Some(true) => {}
+    }
+}
+"#,
+        |cx| {
+            let body = cx.db.body(cx.body);
+            let infer = cx.db.infer(cx.body);
+            let mut patcx = PatCtxt::new(cx.db, cx.body, &infer, &body);
+            let mut reports = body.exprs.iter().filter_map(|(_, expr)| match expr {
+                Expr::Match { expr, arms } => {
+                    let arms: Vec<_> = arms
+                        .iter()
+                        .map(|arm| {
+                            let pat = patcx.lower_pattern(arm.pat);
+                            let pat =
+                                &*cx.pattern_arena.alloc(DeconstructedPat::from_pat(cx, &pat));
+                            MatchArm { pat, has_guard: arm.guard.is_some() }
+                        })
+                        .collect();
+                    Some(compute_match_usefulness(cx, &arms, &infer[*expr]))
+                }
+                _ => None,
+            });
+            let (first, second) = (reports.next().unwrap(), reports.next().unwrap());
+            assert_eq!(first.non_exhaustiveness_witnesses.len(), 2);
+            assert_eq!(second.non_exhaustiveness_witnesses.len(), 1);
+
+            let merged = first.merge(cx, second);
+            assert_eq!(rendered_witnesses(cx, &merged), ["Some(false)"]);
+            let arms: Vec<_> = merged
+                ._arm_usefulness
+                .iter()
+                .map(|(arm, _)| arm.pat.to_pat(cx).display(cx.db).to_string())
+                .collect();
+            assert_eq!(arms, ["Some(true)", "None", "None", "Some(true)"]);
+            assert!(merged.guard_unreachable().is_empty());
+            assert!(!merged.reached_max_depth);
+        },
+    );
+}
+
+#[test]
+fn coverage_diffs() {
+    let diff = |ra_fixture: &str| {
+        with_main_cx(ra_fixture, |cx| {
+            let body = cx.db.body(cx.body);
+            let infer = cx.db.infer(cx.body);
+            let mut patcx = PatCtxt::new(cx.db, cx.body, &infer, &body);
+            let mut matches = body.exprs.iter().filter_map(|(_, expr)| match expr {
+                Expr::Match { arms, .. } => Some(
+                    arms.iter()
+                        .map(|arm| {
+                            let pat = patcx.lower_pattern(arm.pat);
+                            let pat =
+                                &*cx.pattern_arena.alloc(DeconstructedPat::from_pat(cx, &pat));
+                            MatchArm { pat, has_guard: arm.guard.is_some() }
+                        })
+                        .collect::<Vec<_>>(),
+                ),
+                _ => None,
+            });
+            let (a, b) = (matches.next().unwrap(), matches.next().unwrap());
+            let diff = coverage_diff(cx, &a, &b);
+            (
+                rendered_pats(cx, &diff.only_in_a),
+                rendered_pats(cx, &diff.only_in_b),
+                diff.is_empty(),
+            )
+        })
+    };
+
+    let (only_in_a, only_in_b, is_empty) = diff(
+        r#"
+//- minicore: option
+fn main(x: Option<bool>) {
+    match x {
+        Some(_) => {}
+        None => {}
+    }
+    match x {
+        None => {}
+        Some(true) => {}
+        Some(false) => {}
+    }
+}
+"#,
+    );
+    assert!(only_in_a.is_empty() && only_in_b.is_empty() && is_empty);
+
+    let (only_in_a, only_in_b, is_empty) = diff(
+        r#"
+//- minicore: option
+fn main(x: Option<bool>) {
+    match x {
+        Some(_) => {}
+        None => {}
+    }
+    match x {
+        None => {}
+        Some(true) => {}
+    }
+}
+"#,
+    );
+    assert_eq!(only_in_a, ["Some(false)"]);
+    assert!(only_in_b.is_empty());
+    assert!(!is_empty);
+
+    // A guarded arm doesn't count as covering its values.
+    let (only_in_a, only_in_b, _) = diff(
+        r#"
+//- minicore: option
+fn main(x: Option<bool>) {
+    match x {
+        None if true => {}
+        Some(_) => {}
+    }
+    match x {
+        None => {}
+        Some(_) => {}
+    }
+}
+"#,
+    );
+    assert!(only_in_a.is_empty());
+    assert_eq!(only_in_b, ["None"]);
+}
+
+/// Computes the integer coverage of the arms of the first `match` of the function named `main`.
+fn check_int_coverage(ra_fixture: &str, expect: Option<(Vec<(i128, i128)>, Vec<(i128, i128)>)>) {
+    check_match(ra_fixture, |_, report| {
+        let arms: Vec<_> = report._arm_usefulness.iter().map(|(arm, _)| *arm).collect();
+        let coverage = int_coverage(&arms, arms[0].pat.ty()).map(|it| (it.covered, it.gaps));
+        assert_eq!(coverage, expect);
+    });
+}
+
+#[test]
+fn range_overlap_heat_map() {
+    let overlaps = |ra_fixture: &str| {
+        let mut overlaps = Vec::new();
+        check_match(ra_fixture, |_, report| {
+            let arms: Vec<_> = report._arm_usefulness.iter().map(|(arm, _)| *arm).collect();
+            overlaps = range_overlap_map(&arms, arms[0].pat.ty())
+                .unwrap()
+                .into_iter()
+                .map(|it| (it.range, it.arms))
+                .collect();
+        });
+        overlaps
+    };
+
+    assert_eq!(
+        overlaps(
+            r#"
+fn main(x: u8) {
+    match x {
+        0..=100 => {}
+        50..=150 => {}
+        120..=255 => {}
+    }
+}
+"#
+        ),
+        [((50, 100), vec![0, 1]), ((120, 150), vec![1, 2])]
+    );
+    assert_eq!(
+        overlaps(
+            r#"
+fn main(x: i8) {
+    match x {
+        -10..=10 => {}
+        0 | 5 => {}
+        y if y > 0 => {}
+        _ => {}
+    }
+}
+"#
+        ),
+        [
+            ((-10, -1), vec![0, 3]),
+            ((0, 0), vec![0, 1, 3]),
+            ((1, 4), vec![0, 3]),
+            ((5, 5), vec![0, 1, 3]),
+            ((6, 10), vec![0, 3]),
+        ]
+    );
+}
+
+#[test]
+fn int_coverage_fractions() {
+    let fraction = |ra_fixture: &str| {
+        let mut fraction = None;
+        check_match(ra_fixture, |_, report| {
+            let arms: Vec<_> = report._arm_usefulness.iter().map(|(arm, _)| *arm).collect();
+            fraction = int_coverage_fraction(&arms, arms[0].pat.ty());
+        });
+        fraction
+    };
+
+    let half = fraction(
+        r#"
+fn main(x: u8) {
+    match x {
+        0..=127 => {}
+    }
+}
+"#,
+    );
+    assert!((half.unwrap() - 0.5).abs() < 1e-9, "{:?}", half);
+
+    let full = fraction(
+        r#"
+fn main(x: i8) {
+    match x {
+        -128..=-1 => {}
+        0..=127 => {}
+    }
+}
+"#,
+    );
+    assert_eq!(full, Some(1.0));
+
+    let unknown = fraction(
+        r#"
+fn main(x: usize) {
+    match x {
+        0 => {}
+        n => {}
+    }
+}
+"#,
+    );
+    assert_eq!(unknown, None);
+}
+
+#[test]
+fn int_coverage_of_arms() {
+    check_int_coverage(
+        r#"
+fn main(x: u8) {
+    match x {
+        0..=10 => {}
+        20..=30 => {}
+    }
+}
+"#,
+        Some((vec![(0, 10), (20, 30)], vec![(11, 19), (31, 255)])),
+    );
+    check_int_coverage(
+        r#"
+fn main(x: i8) {
+    match x {
+        -99..0 => {}
+        -128..=-100 | 5 => {}
+        3..=6 => {}
+        n if n > 100 => {}
+    }
+}
+"#,
+        Some((vec![(-128, -1), (3, 6)], vec![(0, 2), (7, 127)])),
+    );
+    check_int_coverage(
+        r#"
+fn main(x: u128) {
+    match x {
+        0 => {}
+        n => {}
+    }
+}
+"#,
+        None,
+    );
+    check_int_coverage(
+        &format!("fn main(x: u128) {{ match x {{ 0..{} => {{}} }} }}", u128::MAX),
+        None,
+    );
+    check_int_coverage(
+        r#"
+fn main(x: i128) {
+    match x {
+        0 => {}
+        n => {}
+    }
+}
+"#,
+        Some((vec![(i128::MIN, i128::MAX)], vec![])),
+    );
+    check_int_coverage(
+        r#"
+fn main(x: char) {
+    match x {
+        'a' => {}
+        _ => {}
+    }
+}
+"#,
+        None,
+    );
+}
+
+#[test]
+fn grouped_missing_variants() {
+    let variant_names = |cx: &MatchCheckCtx<'_, '_>, report: &UsefulnessReport<'_>| {
+        let names: Vec<_> = report
+            .missing_variants()
+            .iter()
+            .map(|id| cx.db.enum_data(id.parent).variants[id.local_id].name.to_string())
+            .collect();
+        names
+    };
+    check_match(
+        r#"
+//- minicore: option
+fn main(x: Option<i32>, cond: bool) {
+    match x {
+        Some(_) if cond => {}
+        None if cond => {}
+    }
+}
+"#,
+        |cx, report| {
+            assert_eq!(report.non_exhaustiveness_witnesses.len(), 2);
+            assert_eq!(variant_names(cx, report), ["None", "Some"]);
+        },
+    );
+    check_match(
+        r#"
+enum E { A, B(bool), C }
+fn main(x: E) {
+    match x {
+        E::B(_) => {}
+    }
+}
+"#,
+        |cx, report| assert_eq!(variant_names(cx, report), ["A", "C"]),
+    );
+    // `Some(None)` isn't a bare variant, so nothing is grouped.
+    check_match(
+        r#"
+//- minicore: option
+fn main(x: Option<Option<i32>>) {
+    match x {
+        Some(Some(_)) => {}
+    }
+}
+"#,
+        |cx, report| {
+            assert_eq!(report.non_exhaustiveness_witnesses.len(), 2);
+            assert!(variant_names(cx, report).is_empty());
+        },
+    );
+}
+
+#[test]
+fn witness_strings() {
+    check_match(
+        r#"
+//- minicore: option
+fn main(x: Option<i32>) {
+    match x {
+        Some(_) => {}
+    }
+}
+"#,
+        |cx, report| {
+            let arms: Vec<_> = report._arm_usefulness.iter().map(|(arm, _)| *arm).collect();
+            let scrut_ty = arms[0].pat.ty().clone();
+            assert_eq!(non_exhaustive_witness_strings(cx, &arms, &scrut_ty), ["None"]);
+        },
+    );
+}
+
+#[test]
+fn min_arms_to_complete() {
+    let min_arms = |ra_fixture: &str| {
+        let mut min_arms = 0;
+        check_match(ra_fixture, |_, report| min_arms = report.min_arms_to_complete());
+        min_arms
+    };
+    assert_eq!(
+        min_arms(
+            r#"
+//- minicore: option
+fn main(x: Option<i32>) {
+    match x {}
+}
+"#
+        ),
+        2
+    );
+    assert_eq!(
+        min_arms(
+            r#"
+enum E { A, B, C, D }
+fn main(x: E) {
+    match x {
+        E::B => {}
+    }
+}
+"#
+        ),
+        3
+    );
+    // `(B, _)` and `(C, _)` are two witnesses, but they start with the same constructor.
+    assert_eq!(
+        min_arms(
+            r#"
+enum E { A, B, C }
+fn main(x: (E, bool)) {
+    match x {
+        (E::A, _) => {}
+    }
+}
+"#
+        ),
+        1
+    );
+    assert_eq!(
+        min_arms(
+            r#"
+fn main(x: bool) {
+    match x {
+        true => {}
+        false => {}
+    }
+}
+"#
+        ),
+        0
+    );
+}
+
+#[test]
+fn repr_enum_matched_by_variant() {
+    check_arm_ctors(
+        r#"
+#[repr(u8)]
+enum E { A, B, C }
+fn main(x: E) {
+    match x {
+        E::A => {}
+        E::B => {}
+    }
+}
+"#,
+        |_, _, ctors| {
+            assert!(ctors.iter().all(|ctor| ctor.as_ref().unwrap().as_variant().is_some()));
+        },
+    );
+}
+
+#[test]
+fn check_exhaustive_result() {
+    let check = |ra_fixture: &str| {
+        let mut result = Ok(());
+        check_match(ra_fixture, |cx, report| {
+            let arms: Vec<_> = report._arm_usefulness.iter().map(|(arm, _)| *arm).collect();
+            let scrut_ty = arms[0].pat.ty().clone();
+            result = check_exhaustive(cx, &arms, &scrut_ty).map_err(|witnesses| {
+                witnesses.iter().map(|it| it.display(cx.db).to_string()).collect::<Vec<_>>()
+            });
+        });
+        result
+    };
+    assert_eq!(
+        check(
+            r#"
+fn main(x: bool) {
+    match x {
+        true => {}
+        false => {}
+        true => {}
+    }
+}
+"#
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        check(
+            r#"
+//- minicore: option
+fn main(x: Option<bool>) {
+    match x {
+        Some(true) => {}
+    }
+}
+"#
+        ),
+        Err(vec!["None".to_owned()])
+    );
+}
+
+#[test]
+fn exhaustive_if_arm_added() {
+    check_match(
+        r#"
+//- minicore: option
+fn main(x: Option<i32>) {
+    match x {
+        Some(_) => {}
+    }
+}
+"#,
+        |cx, report| {
+            let arms: Vec<_> = report._arm_usefulness.iter().map(|(arm, _)| *arm).collect();
+            let scrut_ty = arms[0].pat.ty().clone();
+            let some = arms[0].pat.ctor().clone();
+            let enum_id = some.as_variant().unwrap().parent;
+            let (local_id, _) = cx
+                .db
+                .enum_data(enum_id)
+                .variants
+                .iter()
+                .find(|(_, variant)| variant.name.to_string() == "None")
+                .unwrap();
+            let none = Constructor::Variant(EnumVariantId { parent: enum_id, local_id });
+
+            assert!(exhaustive_if_added(cx, &arms, &scrut_ty, none));
+            assert!(!exhaustive_if_added(cx, &arms, &scrut_ty, some));
+        },
+    );
+}
+
+#[test]
+fn exhaustive_without_arm() {
+    check_match(
+        r#"
+//- minicore: option
+fn main(x: Option<i32>) {
+    match x {
+        Some(0) => {}
+        Some(_) => {}
+        Some(1) => {}
+        _ => {}
+    }
+}
+"#,
+        |cx, report| {
+            let arms: Vec<_> = report._arm_usefulness.iter().map(|(arm, _)| *arm).collect();
+            let scrut_ty = arms[0].pat.ty().clone();
+            // Removing the catch-all leaves `None` uncovered.
+            assert!(!exhaustive_without(cx, &arms, &scrut_ty, 3));
+            // The other arms are redundant with it.
+            assert!(exhaustive_without(cx, &arms, &scrut_ty, 0));
+            assert!(exhaustive_without(cx, &arms, &scrut_ty, 1));
+            assert!(exhaustive_without(cx, &arms, &scrut_ty, 2));
+        },
+    );
+}
+
+#[test]
+fn witnesses_rendered_with_shared_names() {
+    check_match(
+        r#"
+enum E { A, B(bool), C { x: u8 }, D }
+fn main(x: (E, E)) {
+    match x {
+        (E::A, _) => {}
+    }
+}
+"#,
+        |cx, report| {
+            let witnesses = &report.non_exhaustiveness_witnesses;
+            let names = PatNameCache::default();
+            let rendered = render_witnesses(cx, witnesses, &names);
+            assert_eq!(rendered, ["(B(_), _)", "(C { .. }, _)", "(D, _)"]);
+            assert_eq!(names.len(), 3);
+
+            // Rendering the witnesses again reuses the names looked up the first time.
+            assert_eq!(render_witnesses(cx, witnesses, &names), rendered);
+            assert_eq!(names.len(), 3);
+        },
+    );
+}
+
+#[test]
+fn overlapping_ranges() {
+    check_match(
+        r#"
+fn main(x: u8) {
+    match x {
+        0..=200 => {}
+        100..=255 => {}
+        150 => {}
+    }
+}
+"#,
+        |_, report| {
+            assert_eq!(report.range_overlaps(), [(0, 1, (100, 200))]);
+        },
+    );
+    check_match(
+        r#"
+fn main(x: i8) {
+    match x {
+        -128..=-100 => {}
+        5 => {}
+        -110..=10 => {}
+        _ => {}
+    }
+}
+"#,
+        |_, report| {
+            assert_eq!(report.range_overlaps(), [(0, 2, (-110, -100))]);
+        },
+    );
+}
+
+#[test]
+fn all_arms_guarded() {
+    check_match(
+        r#"
+fn main(b: bool, c: bool, d: bool) {
+    match b {
+        true if c => {}
+        false if d => {}
+    }
+}
+"#,
+        |cx, report| {
+            assert!(!report.non_exhaustiveness_witnesses.is_empty());
+            let reason = report.non_exhaustive_reason(cx);
+            assert_eq!(reason, Some(NonExhaustiveReason::AllArmsGuarded));
+        },
+    );
+    check_match(
+        r#"
+fn main(b: bool, c: bool) {
+    match b {
+        true if c => {}
+        true => {}
+    }
+}
+"#,
+        |cx, report| {
+            let reason = report.non_exhaustive_reason(cx);
+            assert_eq!(reason, Some(NonExhaustiveReason::MissingVariants));
+        },
+    );
+}
+
+#[test]
+fn record_pattern_fields_align_with_declaration() {
+    check_match(
+        r#"
+//- minicore: option
+struct Foo { a: bool, b: Option<u8> }
+fn main(x: Foo) {
+    match x {
+        Foo { a: true, .. } => {}
+        Foo { b: None, .. } => {}
+        Foo { b: Some(_), a: false } => {}
+    }
+}
+"#,
+        |cx, report| {
+            let fields: Vec<Vec<_>> = report
+                ._arm_usefulness
+                .iter()
+                .map(|(arm, _)| {
+                    arm.pat
+                        .iter_fields()
+                        .map(|field| {
+                            let pat = field.to_pat(cx).display(cx.db).to_string();
+                            (pat, field.ty().display(cx.db).to_string())
+                        })
+                        .collect()
+                })
+                .collect();
+            let field = |pat: &str, ty: &str| (pat.to_owned(), ty.to_owned());
+            assert_eq!(
+                fields,
+                [
+                    [field("true", "bool"), field("_", "Option<u8>")],
+                    [field("_", "bool"), field("None", "Option<u8>")],
+                    [field("false", "bool"), field("Some(_)", "Option<u8>")],
+                ]
+            );
+            assert!(report.non_exhaustiveness_witnesses.is_empty());
+        },
+    );
+}
+
+#[test]
+fn max_witnesses() {
+    let fixture = r#"
+enum E { A, B, C, D, E }
+fn main(x: (E, bool)) {
+    match x {
+        (E::A, _) => {}
+    }
+}
+"#;
+    check_match(fixture, |cx, report| {
+        assert_eq!(rendered_witnesses(cx, report), ["(B, _)", "(C, _)", "(D, _)", "(E, _)"]);
+        assert_eq!(
+            uncovered_patterns(cx, &report.non_exhaustiveness_witnesses),
+            "`(B, _)`, `(C, _)`, `(D, _)` and 1 more not covered"
+        );
+    });
+    check_match_with(
+        fixture,
+        |cx| cx.config.max_witnesses = Some(2),
+        |cx, report| {
+            assert_eq!(rendered_witnesses(cx, report), ["(B, _)", "(C, _)"]);
+        },
+    );
+    check_match_with(
+        fixture,
+        |cx| cx.config.max_witnesses = Some(4),
+        |cx, report| {
+            assert_eq!(
+                uncovered_patterns(cx, &report.non_exhaustiveness_witnesses),
+                "`(B, _)`, `(C, _)`, `(D, _)` and at least 1 more not covered"
+            );
+        },
+    );
+}
+
+#[test]
+fn record_variant_rest_patterns() {
+    check_match(
+        r#"
+enum E { V { a: u8, b: bool, c: char }, W }
+fn main(e: E) {
+    match e {
+        E::V { a: 0, .. } => {}
+        E::V { c: 'x', b: true, .. } => {}
+        E::V { .. } => {}
+        E::V { b: false, a: 1, .. } => {}
+        E::W => {}
+    }
+}
+"#,
+        |cx, report| {
+            let fields: Vec<Vec<_>> = report
+                ._arm_usefulness
+                .iter()
+                .map(|(arm, _)| {
+                    arm.pat
+                        .iter_fields()
+                        .map(|field| {
+                            let pat = field.to_pat(cx).display(cx.db).to_string();
+                            (pat, field.ty().display(cx.db).to_string())
+                        })
+                        .collect()
+                })
+                .collect();
+            let field = |pat: &str, ty: &str| (pat.to_owned(), ty.to_owned());
+            assert_eq!(
+                fields,
+                [
+                    vec![field("0", "u8"), field("_", "bool"), field("_", "char")],
+                    vec![field("_", "u8"), field("true", "bool"), field("_", "char")],
+                    vec![field("_", "u8"), field("_", "bool"), field("_", "char")],
+                    vec![field("1", "u8"), field("false", "bool"), field("_", "char")],
+                    vec![],
+                ]
+            );
+            let reachable: Vec<_> = report
+                ._arm_usefulness
+                .iter()
+                .map(|(_, reachability)| matches!(reachability, Reachability::Reachable))
+                .collect();
+            assert_eq!(reachable, [true, true, true, false, true]);
+            assert!(report.non_exhaustiveness_witnesses.is_empty());
+        },
+    );
+}
+
+#[test]
+fn usefulness_stats() {
+    let fixture = r#"
+fn main(x: (u8, bool)) {
+    match x {
+        (0..=10, _) => {}
+        (11..=100, true) => {}
+        (11..=100, false) => {}
+        (101..=255, _) => {}
+    }
+}
+"#;
+    check_match(fixture, |cx, _| assert!(cx.take_stats().is_none()));
+    check_match_with(
+        fixture,
+        |cx| cx.collect_stats(),
+        |cx, _| {
+            let stats = cx.take_stats().unwrap();
+            // The witness pass splits the integer column, in which the arms have 3 distinct
+            // ranges, into those 3 ranges.
+            let int_split = stats
+                .splits
+                .iter()
+                .rev()
+                .find(|split| split.ty.display(cx.db).to_string() == "u8")
+                .unwrap();
+            assert_eq!((int_split.distinct_ctors, int_split.split_ctors), (3, 3));
+            assert_eq!(stats.distinct_ctors_histogram().get(&3), Some(&1));
+
+            assert!(cx.take_stats().unwrap().splits.is_empty());
+        },
+    );
+}
+
+#[test]
+fn first_arm_against_empty_matrix() {
+    check_match_with(
+        r#"
+struct S { a: bool, b: u8 }
+fn main(x: S) {
+    match x {
+        S { a: true, b: 0..=9 } => {}
+        S { .. } => {}
+    }
+}
+"#,
+        |cx| cx.collect_stats(),
+        |cx, report| {
+            let stats = cx.take_stats().unwrap();
+            // The first arm is checked against an empty matrix: the column type comes from the arm
+            // itself, and its constructor splits into just itself.
+            let first = &stats.splits[0];
+            assert_eq!(first.ty.display(cx.db).to_string(), "S");
+            assert_eq!((first.distinct_ctors, first.split_ctors), (0, 1));
+            let second = &stats.splits[1];
+            assert_eq!(second.ty.display(cx.db).to_string(), "S");
+            assert_eq!((second.distinct_ctors, second.split_ctors), (1, 1));
+
+            let (arm, reachability) = &report._arm_usefulness[0];
+            assert!(reachability.is_reachable());
+            assert!(arm.pat.iter_fields().all(|field| field.is_reachable()));
+            assert!(report._arm_usefulness[1].1.is_reachable());
+            assert!(report.non_exhaustiveness_witnesses.is_empty());
+        },
+    );
+}
+
+#[test]
+fn max_depth() {
+    let depth = 100;
+    let pat = format!("{}true{}", "Some(".repeat(depth), ")".repeat(depth));
+    let fixture = format!(
+        r#"
+//- minicore: option
+fn main(x: {}bool{}) {{
+    match x {{
+        {pat} => {{}}
+        {pat} => {{}}
+        _ => {{}}
+    }}
+}}
+"#,
+        "Option<".repeat(depth),
+        ">".repeat(depth),
+        pat = pat,
+    );
+    let reachable = |report: &UsefulnessReport<'_>| -> Vec<_> {
+        report
+            ._arm_usefulness
+            .iter()
+            .map(|(_, reachability)| matches!(reachability, Reachability::Reachable))
+            .collect()
+    };
+    check_match(&fixture, |_, report| {
+        assert!(!report.reached_max_depth);
+        assert_eq!(reachable(report), [true, false, true]);
+        assert!(report.non_exhaustiveness_witnesses.is_empty());
+    });
+    // Past the limit, the duplicate arm is assumed to be reachable rather than checked.
+    check_match_with(
+        &fixture,
+        |cx| cx.config.max_depth = Some(16),
+        |_, report| {
+            assert!(report.reached_max_depth);
+            assert_eq!(reachable(report), [true, true, true]);
+            assert!(report.non_exhaustiveness_witnesses.is_empty());
+        },
+    );
+    check_match_with(
+        &fixture,
+        |cx| cx.config.max_depth = Some(depth + 1),
+        |_, report| {
+            assert!(!report.reached_max_depth);
+            assert_eq!(reachable(report), [true, false, true]);
+        },
+    );
+}
+
+#[test]
+fn missing_arms_past_max_depth() {
+    let uncovered_patterns = |depth: usize| {
+        let fixture = format!(
+            r#"
+//- minicore: option
+fn main(x: {}bool{}) {{
+    match x {{
+        {}true{} => {{}}
+    }}
+}}
+"#,
+            "Option<".repeat(depth),
+            ">".repeat(depth),
+            "Some(".repeat(depth),
+            ")".repeat(depth),
+        );
+        let (db, owner) = main_fn(&fixture);
+        let body = db.body(owner);
+        let (match_expr, _) =
+            body.exprs.iter().find(|(_, expr)| matches!(expr, Expr::Match { .. })).unwrap();
+        db.match_check(owner, match_expr).unwrap().uncovered_patterns.clone()
+    };
+    assert!(uncovered_patterns(10).is_some());
+    // Past the default limit, the witnesses may be incomplete, so the match isn't reported.
+    assert_eq!(uncovered_patterns(300), None);
+}
+
+#[test]
+fn witness_sinks() {
+    #[derive(Default)]
+    struct CountConstructors(usize);
+    impl<'p> WitnessSink<'p> for CountConstructors {
+        type Output = usize;
+        fn push_constructor(&mut self, _: &MatchCheckCtx<'_, 'p>, _: &DeconstructedPat<'p>) {
+            self.0 += 1;
+        }
+        fn finish(self) -> usize {
+            self.0
+        }
+    }
+
+    check_match(
+        r#"
+//- minicore: option
+fn main(x: Option<Option<bool>>) {
+    match x {
+        Some(Some(true)) => {}
+        Some(None) => {}
+        None => {}
+    }
+}
+"#,
+        |cx, report| {
+            let pats: Vec<_> = report
+                .feed_witnesses(cx, PatWitnessSink::default())
+                .iter()
+                .map(|pat| pat.display(cx.db).to_string())
+                .collect();
+            let witnesses = rendered_witnesses(cx, report);
+            assert_eq!(pats, witnesses);
+            assert_eq!(pats, ["Some(Some(false))"]);
+
+            // `Some`, `Some` and `false`.
+            assert_eq!(report.feed_witnesses(cx, CountConstructors::default()), 3);
+        },
+    );
+}
+
+#[test]
+fn arm_coverage() {
+    check_match(
+        r#"
+//- minicore: option
+fn main(x: Option<bool>) {
+    match x {
+        Some(true) => {}
+        _ => {}
+        None => {}
+    }
+}
+"#,
+        |cx, report| {
+            let arms: Vec<_> = report._arm_usefulness.iter().map(|(arm, _)| *arm).collect();
+            let coverage: Vec<Vec<_>> = arm_coverage_counts(cx, &arms)
+                .iter()
+                .map(|estimate| {
+                    estimate
+                        .ctors
+                        .iter()
+                        .map(|ctor| {
+                            let id = ctor.as_variant().unwrap();
+                            cx.db.enum_data(id.parent).variants[id.local_id].name.to_string()
+                        })
+                        .collect()
+                })
+                .collect();
+            assert_eq!(coverage, [vec!["Some"], vec!["Some", "None"], vec![]]);
+        },
+    );
+}
+
+#[test]
+fn arm_coverage_leaves_reachability_alone() {
+    fn any_reachable(pat: &DeconstructedPat<'_>) -> bool {
+        pat.is_reachable() || pat.iter_fields().any(any_reachable)
+    }
+    with_main_cx(
+        r#"
+//- minicore: option
+fn main(x: Option<bool>) {
+    match x {
+        Some(true) => {}
+        Some(true | false) => {}
+        None => {}
+    }
+}
+"#,
+        |cx| {
+            let (_, arms) = lower_first_match(cx);
+            arm_coverage_counts(cx, &arms);
+            assert!(!arms.iter().any(|arm| any_reachable(arm.pat)));
+        },
+    );
+}
+
+#[test]
+fn wildcard_after_all_variants_of_non_exhaustive_enum() {
+    let unreachable_arms = |ra_fixture| {
+        let mut unreachable = Vec::new();
+        check_match(ra_fixture, |_, report| {
+            unreachable = report
+                ._arm_usefulness
+                .iter()
+                .map(|(_, reachability)| matches!(reachability, Reachability::Unreachable))
+                .collect();
+            assert!(report.non_exhaustiveness_witnesses.is_empty());
+        });
+        unreachable
+    };
+
+    // Other crates may add variants to a `#[non_exhaustive]` enum, so the `_` arm is needed.
+    let foreign = unreachable_arms(
+        r#"
+//- /main.rs crate:main deps:lib
+fn main(x: lib::E) {
+    match x {
+        lib::E::A => {}
+        lib::E::B => {}
+        _ => {}
+    }
+}
+//- /lib.rs crate:lib
+#[non_exhaustive]
+pub enum E { A, B }
+"#,
+    );
+    assert_eq!(foreign, [false, false, false]);
+
+    // In its own crate, the enum is matched like any other.
+    let local = unreachable_arms(
+        r#"
+#[non_exhaustive]
+enum E { A, B }
+fn main(x: E) {
+    match x {
+        E::A => {}
+        E::B => {}
+        _ => {}
+    }
+}
+"#,
+    );
+    assert_eq!(local, [false, false, true]);
+}
+
+#[test]
+fn split_wildcard_present_and_missing() {
+    check_match(
+        r#"
+//- minicore: option
+fn main(x: Option<i32>) {
+    match x {
+        Some(_) => {}
+    }
+}
+"#,
+        |cx, report| {
+            let pat = report._arm_usefulness[0].0.pat;
+            let pcx = usefulness::PatCtxt {
+                cx,
+                ty: pat.ty(),
+                is_top_level: true,
+                is_non_exhaustive: false,
+            };
+            let mut split_wildcard = SplitWildcard::new(pcx);
+            split_wildcard.split(pcx, std::iter::once(pat.ctor()));
+
+            let names = |ctors: Vec<&Constructor>| -> Vec<_> {
+                ctors
+                    .into_iter()
+                    .map(|ctor| {
+                        let id = ctor.as_variant().unwrap();
+                        cx.db.enum_data(id.parent).variants[id.local_id].name.to_string()
+                    })
+                    .collect()
+            };
+            assert_eq!(names(split_wildcard.iter_present(pcx).collect()), ["Some"]);
+            assert_eq!(names(split_wildcard.iter_missing(pcx).collect()), ["None"]);
+        },
+    );
+}
+
+#[test]
+fn variant_split_is_identity() {
+    with_main_cx(
+        r#"
+//- minicore: option
+fn main(x: Option<i32>) {
+    match x {}
+}
+"#,
+        |cx| {
+            let body = cx.db.body(cx.body);
+            let infer = cx.db.infer(cx.body);
+            let (scrutinee, _) = first_match(&body);
+            let pcx = usefulness::PatCtxt {
+                cx,
+                ty: &infer[scrutinee],
+                is_top_level: true,
+                is_non_exhaustive: false,
+            };
+
+            let variants: Vec<_> = SplitWildcard::new(pcx).iter_missing(pcx).cloned().collect();
+            // Splitting a variant must not look at the column at all.
+            let head_ctors = variants.iter().inspect(|_| panic!("scanned the head constructors"));
+            for variant in &variants {
+                assert_eq!(&variant.split(pcx, head_ctors.clone())[..], [variant.clone()]);
+            }
+        },
+    );
+}
+
+#[test]
+fn split_wildcard_all_missing() {
+    with_main_cx(
+        r#"
+//- minicore: option
+fn main(x: Option<i32>) {
+    match x {}
+}
+"#,
+        |cx| {
+            let body = cx.db.body(cx.body);
+            let infer = cx.db.infer(cx.body);
+            let (scrutinee, _) = first_match(&body);
+            let pcx = usefulness::PatCtxt {
+                cx,
+                ty: &infer[scrutinee],
+                is_top_level: true,
+                is_non_exhaustive: false,
+            };
+
+            // An empty match has no constructors in its column.
+            let mut split_wildcard = SplitWildcard::new(pcx);
+            split_wildcard.split(pcx, std::iter::empty());
+            assert!(split_wildcard.all_missing(pcx));
+            assert_eq!(split_wildcard.iter_missing(pcx).count(), 2);
+
+            let variant = SplitWildcard::new(pcx).iter_missing(pcx).next().unwrap().clone();
+            let mut split_wildcard = SplitWildcard::new(pcx);
+            split_wildcard.split(pcx, std::iter::once(&variant));
+            assert!(!split_wildcard.all_missing(pcx));
+            assert_eq!(split_wildcard.iter_missing(pcx).count(), 1);
+        },
+    );
+}
+
+#[test]
+fn range_pattern_of_struct_type() {
+    check_match(
+        r#"
+struct S { a: u8 }
+fn main(x: S) {
+    match x {
+        _ => {}
+    }
+}
+"#,
+        |cx, report| {
+            let ty = report._arm_usefulness[0].0.pat.ty().clone();
+            let range = Pat {
+                ty: ty.clone(),
+                kind: Box::new(PatKind::Range {
+                    lo: Literal::Uint(0, None),
+                    hi: Literal::Uint(5, None),
+                }),
+            };
+            let range = cx.pattern_arena.alloc(DeconstructedPat::from_pat(cx, &range));
+            assert_eq!(range.ctor(), &Constructor::Opaque);
+
+            // The malformed arm covers nothing, like other patterns we can't compare.
+            let wild = cx.pattern_arena.alloc(DeconstructedPat::wildcard(ty.clone()));
+            let arms = [
+                MatchArm { pat: range, has_guard: false },
+                MatchArm { pat: wild, has_guard: false },
+            ];
+            let report = compute_match_usefulness(cx, &arms, &ty);
+            assert!(report._arm_usefulness.iter().all(|(_, it)| it.is_reachable()));
+            let report = compute_match_usefulness(cx, &arms[..1], &ty);
+            assert!(!report.non_exhaustiveness_witnesses.is_empty());
+        },
+    );
+}
+
+#[test]
+fn unresolved_scrutinee_type() {
+    with_main_cx("fn main() {}", |cx| {
+        let ty =
+            TyKind::InferenceVar(InferenceVar::from(0), TyVariableKind::General).intern(Interner);
+        let pcx = usefulness::PatCtxt { cx, ty: &ty, is_top_level: true, is_non_exhaustive: false };
+
+        let mut split_wildcard = SplitWildcard::new(pcx);
+        split_wildcard.split(pcx, std::iter::empty());
+        let missing: Vec<_> = split_wildcard.iter_missing(pcx).collect();
+        assert_eq!(missing, [&Constructor::NonExhaustive]);
+
+        // Only a wildcard covers a value of a type we know nothing about.
+        let report = compute_match_usefulness(cx, &[], &ty);
+        assert_eq!(rendered_witnesses(cx, &report), ["_"]);
+        let wild = MatchArm {
+            pat: cx.pattern_arena.alloc(DeconstructedPat::wildcard(ty.clone())),
+            has_guard: false,
+        };
+        let report = compute_match_usefulness(cx, &[wild], &ty);
+        assert!(report.non_exhaustiveness_witnesses.is_empty());
+    });
+}
+
+#[test]
+fn non_exhaustive_reasons() {
+    let check = |ra_fixture, reason| {
+        check_match(ra_fixture, |cx, report| assert_eq!(report.non_exhaustive_reason(cx), reason))
+    };
+    check(
+        r#"
+enum E { A, B }
+fn main(x: E) {
+    match x {
+        E::A => {}
+        E::B => {}
+    }
+}
+"#,
+        None,
+    );
+    check(
+        r#"
+enum E { A, B }
+fn main(x: E) {
+    match x {
+        E::A => {}
+    }
+}
+"#,
+        Some(NonExhaustiveReason::MissingVariants),
+    );
+    check(
+        r#"
+//- /main.rs crate:main deps:lib
+fn main(x: lib::E) {
+    match x {
+        lib::E::A => {}
+    }
+}
+//- /lib.rs crate:lib
+#[non_exhaustive]
+pub enum E { A }
+"#,
+        Some(NonExhaustiveReason::ForeignNonExhaustive),
+    );
+    check(
+        r#"
+enum Void {}
+enum Opt { Some(Void), None }
+fn main(x: Opt) {
+    match x {
+        Opt::None => {}
+    }
+}
+"#,
+        Some(NonExhaustiveReason::UninhabitedNotFeatureGated),
+    );
+    check(
+        r#"
+fn main(x: &str, cond: bool) {
+    match x {
+        _ if cond => {}
+    }
+}
+"#,
+        Some(NonExhaustiveReason::InfiniteType(InfiniteType::Str)),
+    );
+    check(
+        r#"
+fn main(x: usize) {
+    match x {
+        0 => {}
+    }
+}
+"#,
+        Some(NonExhaustiveReason::InfiniteType(InfiniteType::Int)),
+    );
+    check(
+        r#"
+fn main(x: f64, cond: bool) {
+    match x {
+        _ if cond => {}
+    }
+}
+"#,
+        Some(NonExhaustiveReason::InfiniteType(InfiniteType::Float)),
+    );
+    check(
+        r#"
+fn main<T>(x: T, cond: bool) {
+    match x {
+        _ if cond => {}
+    }
+}
+"#,
+        Some(NonExhaustiveReason::UnknownConstructors),
+    );
+}
+
+#[test]
+fn example_witness() {
+    check_match(
+        r#"
+enum Opt { Some(i32), None }
+fn main(x: Opt) {
+    match x {
+        Opt::None => {}
+    }
+}
+"#,
+        |cx, report| {
+            let witness = &report.non_exhaustiveness_witnesses[0];
+            assert_eq!(witness.to_pat(cx).display(cx.db).to_string(), "Some(_)");
+            assert_eq!(witness.to_example_pat(cx).display(cx.db).to_string(), "Some(0)");
+        },
+    );
+}
+
+#[test]
+fn new_context_checks_a_trivial_match() {
+    with_main_cx("fn main() {}", |cx| {
+        assert!(cx.wildcards_cache.borrow().is_empty());
+
+        let pat = cx.pattern_arena.alloc(DeconstructedPat::wildcard(TyBuilder::unit()));
+        let arms = [MatchArm { pat, has_guard: false }];
+        let report = compute_match_usefulness(cx, &arms, &TyBuilder::unit());
+        assert!(matches!(report._arm_usefulness[0].1, Reachability::Reachable));
+        assert!(report.non_exhaustiveness_witnesses.is_empty());
+    });
+}
+
+#[test]
+fn witness_shorter_than_arity_is_unchanged() {
+    with_main_cx("fn main(x: (bool, bool)) {}", |cx| {
+        let body = cx.db.body(cx.body);
+        let infer = cx.db.infer(cx.body);
+        let ty = &infer[body.params[0]];
+        let pcx = usefulness::PatCtxt { cx, ty, is_top_level: true, is_non_exhaustive: false };
+        let bool = TyKind::Scalar(Scalar::Bool).intern(Interner);
+
+        let witness = Witness(vec![DeconstructedPat::wildcard(bool.clone())]);
+        let witness = witness.try_apply_constructor(pcx, &Constructor::Single).err().unwrap();
+        assert_eq!(witness.0.len(), 1);
+        assert!(witness.0[0].ctor().is_wildcard());
+
+        let witness = Witness(vec![
+            DeconstructedPat::wildcard(bool.clone()),
+            witness.0[0].clone_and_forget_reachability(),
+        ]);
+        let witness = witness.try_apply_constructor(pcx, &Constructor::Single).ok().unwrap();
+        assert_eq!(witness.0.len(), 1);
+        assert_eq!(witness.0[0].ctor(), &Constructor::Single);
+    });
+}
+
+#[test]
+fn malformed_witness_is_skipped() {
+    with_main_cx("fn main() {}", |cx| {
+        let unit = || DeconstructedPat::wildcard(TyBuilder::unit());
+
+        let witnesses = [Witness(Vec::new()), Witness(vec![unit(), unit()]), Witness(vec![unit()])];
+        let witnesses: Vec<_> =
+            witnesses.into_iter().filter_map(Witness::into_single_pattern).collect();
+        assert_eq!(witnesses.len(), 1);
+        assert_eq!(witnesses[0].to_pat(cx).display(cx.db).to_string(), "_");
+    });
+}
+
+#[test]
+fn duplicate_witnesses_are_removed() {
+    with_main_cx("fn main() {}", |cx| {
+        let unit = TyBuilder::unit;
+        let bool = || TyKind::Scalar(Scalar::Bool).intern(Interner);
+
+        let witnesses = [unit(), bool(), unit(), bool()].map(DeconstructedPat::wildcard).into();
+        let tys: Vec<_> = dedup_witnesses(cx, witnesses).iter().map(|it| it.ty().clone()).collect();
+        assert_eq!(tys, [unit(), bool()]);
+
+        // Patterns compare their binding names, but those don't make two witnesses different.
+        let binding = |idx| Pat {
+            ty: unit(),
+            kind: Box::new(PatKind::Binding { name: Name::new_tuple_field(idx), subpattern: None }),
+        };
+        assert_ne!(binding(0), binding(1));
+        assert_eq!(WitnessKey::new(&binding(0)), WitnessKey::new(&binding(1)));
+        assert_eq!(
+            WitnessKey::new(&binding(0)),
+            WitnessKey::new(&Pat { ty: unit(), kind: Box::new(PatKind::Wild) })
+        );
+    });
+}
+
+#[test]
+fn bool_literal_patterns() {
+    with_main_cx("fn main() {}", |cx| {
+        let bool = || TyKind::Scalar(Scalar::Bool).intern(Interner);
+        let lit = |value| {
+            let pat =
+                Pat { ty: bool(), kind: Box::new(PatKind::Lit { value: Literal::Bool(value) }) };
+            let pat = &*cx.pattern_arena.alloc(DeconstructedPat::from_pat(cx, &pat));
+            MatchArm { pat, has_guard: false }
+        };
+
+        let literal_bool = Pat { ty: bool(), kind: Box::new(PatKind::LiteralBool { value: true }) };
+        assert_eq!(lit(true).pat.ctor(), DeconstructedPat::from_pat(cx, &literal_bool).ctor());
+
+        let report = compute_match_usefulness(cx, &[lit(true)], &bool());
+        assert_eq!(rendered_witnesses(cx, &report), ["false"]);
+
+        let report = compute_match_usefulness(cx, &[lit(true), lit(false)], &bool());
+        assert!(report.non_exhaustiveness_witnesses.is_empty());
+    });
+}
+
+#[test]
+fn ragged_matrix_row_is_skipped() {
+    let arena = Arena::new();
+    let unit = &*arena.alloc(DeconstructedPat::wildcard(TyBuilder::unit()));
+
+    let mut matrix = Matrix::empty();
+    assert_eq!(matrix.try_push(PatStack::from_vec(smallvec![unit, unit])), Ok(()));
+    assert_eq!(
+        matrix.try_push(PatStack::from_vec(smallvec![unit])),
+        Err(InvalidRow::ColumnCount { row: 1, matrix: 2 })
+    );
+    assert_eq!(matrix.try_push(PatStack::from_vec(smallvec![unit, unit])), Ok(()));
+    assert_eq!(matrix.column_count(), Some(2));
+    assert_eq!(matrix.heads().count(), 2);
+}
+
+#[test]
+fn missing_ctor_in_matrix() {
+    let arena = Arena::new();
+    let missing = Constructor::Missing { nonexhaustive_enum_missing_real_variants: false };
+    let missing = &*arena.alloc(DeconstructedPat::new(missing, Fields::empty(), TyBuilder::unit()));
+
+    let mut matrix = Matrix::empty();
+    let row = PatStack::from_vec(smallvec![missing]);
+    assert_eq!(matrix.try_push(row), Err(InvalidRow::MissingCtor));
+    assert_eq!(matrix.column_count(), None);
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn debug_matrix() {
+    with_main_cx("fn main() {}", |cx| {
+        let bool = || TyKind::Scalar(Scalar::Bool).intern(Interner);
+        let lit = |value| {
+            let pat =
+                Pat { ty: bool(), kind: Box::new(PatKind::Lit { value: Literal::Bool(value) }) };
+            &*cx.pattern_arena.alloc(DeconstructedPat::from_pat(cx, &pat))
+        };
+        let wild = &*cx.pattern_arena.alloc(DeconstructedPat::wildcard(bool()));
+
+        let mut matrix = Matrix::empty();
+        matrix.push(PatStack::from_vec(smallvec![lit(true), wild]));
+        matrix.push(PatStack::from_vec(smallvec![wild, lit(false)]));
+        let rows: Vec<_> = format!("{:?}", matrix).lines().skip(1).map(str::to_owned).collect();
+        assert_eq!(
+            rows,
+            [
+                "+ IntRange(IntRange { range: 1..=1 }) + _ +",
+                "+ _ + IntRange(IntRange { range: 0..=0 }) +",
+            ]
+        );
+    });
+}
+
+#[test]
+fn let_chain_with_leading_irrefutable_let() {
+    with_main_cx(
+        r#"
+enum Opt { Some(i32), None }
+fn main(pair: (i32, i32), opt: Opt) {
+    if let (a, b) = pair && let Opt::Some(c) = opt {}
+}
+"#,
+        |cx| {
+            let body = cx.db.body(cx.body);
+            let lets: Vec<_> = body
+                .exprs
+                .iter()
+                .filter_map(|(_, expr)| match expr {
+                    Expr::Let { pat, expr } => Some((*pat, *expr)),
+                    _ => None,
+                })
+                .collect();
+
+            let report = check_let_chain(cx, &lets);
+            assert_eq!(report.irrefutable, [true, false]);
+            assert!(!report.is_else_unreachable());
+        },
+    );
+}
+
+#[test]
+fn let_chain_with_mismatched_pattern() {
+    with_main_cx(
+        r#"
+fn main(pair: (i32, i32), r: &(i32, i32)) {
+    if let (a, b, c) = pair && let (d, e) = r {}
+}
+"#,
+        |cx| {
+            let body = cx.db.body(cx.body);
+            let lets: Vec<_> = body
+                .exprs
+                .iter()
+                .filter_map(|(_, expr)| match expr {
+                    Expr::Let { pat, expr } => Some((*pat, *expr)),
+                    _ => None,
+                })
+                .collect();
+
+            // A pattern that doesn't fit its scrutinee isn't checked, but a reference scrutinee
+            // is dereferenced like in a match.
+            let report = check_let_chain(cx, &lets);
+            assert_eq!(report.irrefutable, [false, true]);
+            assert!(!report.is_else_unreachable());
+        },
+    );
+}
+
+#[test]
+fn refutable_closure_parameters() {
+    with_main_cx(
+        r#"
+enum Opt { Some(i32), None }
+fn main() {
+    let f = |Opt::Some(x): Opt| x;
+    let g = |(a, b): (i32, i32)| a + b;
+}
+"#,
+        |cx| {
+            let body = cx.db.body(cx.body);
+            let infer = cx.db.infer(cx.body);
+            let params: Vec<_> = body
+                .exprs
+                .iter()
+                .filter_map(|(_, expr)| match expr {
+                    Expr::Closure { args, .. } => Some(args[0]),
+                    _ => None,
+                })
+                .collect();
+            let check = |pat| check_irrefutable(cx, pat, &infer[pat], PatternContext::FnParam);
+
+            let refutable = check(params[0]).unwrap();
+            assert_eq!(
+                refutable.context.refutable_message(),
+                "refutable pattern in function parameter"
+            );
+            assert_eq!(rendered_pats(cx, &refutable.witnesses), ["None"]);
+            assert!(check(params[1]).is_none());
+        },
+    );
+}
+
+#[test]
+fn or_patterns_in_let_bindings() {
+    with_main_cx(
+        r#"
+enum Res { Ok(i32), Err(i32) }
+enum Opt { Some(i32), None }
+fn main(r: Res, o: Opt, p: Opt) {
+    let (Res::Ok(x) | Res::Err(x)) = r;
+    let (Opt::Some(y) | Opt::None) = o;
+    let (Opt::Some(z) | Opt::Some(z)) = p;
+}
+"#,
+        |cx| {
+            let body = cx.db.body(cx.body);
+            let infer = cx.db.infer(cx.body);
+            let pats: Vec<_> = body
+                .exprs
+                .iter()
+                .filter_map(|(_, expr)| match expr {
+                    Expr::Block { statements, .. } => Some(statements),
+                    _ => None,
+                })
+                .flat_map(|statements| statements.iter())
+                .filter_map(|stmt| match *stmt {
+                    Statement::Let { pat, .. } => Some(pat),
+                    _ => None,
+                })
+                .collect();
+            let check = |pat| check_irrefutable(cx, pat, &infer[pat], PatternContext::LetBinding);
+
+            // The alternatives jointly cover every value, whatever they bind.
+            assert!(check(pats[0]).is_none());
+            assert!(check(pats[1]).is_none());
+            let refutable = check(pats[2]).unwrap();
+            assert_eq!(rendered_pats(cx, &refutable.witnesses), ["None"]);
+        },
+    );
+}
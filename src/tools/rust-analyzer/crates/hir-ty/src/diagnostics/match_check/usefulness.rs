@@ -271,37 +271,133 @@
 //! The details are not necessary to understand this file, so we explain them in
 //! [`super::deconstruct_pat`]. Splitting is done by the [`Constructor::split`] function.
 
-use std::iter::once;
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    iter::once,
+};
 
-use hir_def::{AdtId, DefWithBodyId, HasModule, ModuleId};
+use hir_def::{AdtId, DefWithBodyId, HasModule, ModuleId, StructId};
+use rustc_hash::{FxHashMap, FxHashSet};
 use smallvec::{smallvec, SmallVec};
+use stdx::never;
 use typed_arena::Arena;
 
-use crate::{db::HirDatabase, inhabitedness::is_ty_uninhabited_from, Ty, TyExt};
+use crate::{
+    db::HirDatabase, display::HirDisplay, inhabitedness::is_ty_uninhabited_from, Interner, Ty,
+    TyExt, TyKind,
+};
 
-use super::deconstruct_pat::{Constructor, DeconstructedPat, Fields, SplitWildcard};
+use super::{
+    deconstruct_pat::{Constructor, DeconstructedPat, Fields, SplitWildcard},
+    normalize_pat_ty, Pat, PatKind, PatNameCache, PatternFoldable, PatternFolder,
+};
+
+#[cfg(test)]
+use std::{collections::BTreeMap, mem};
+
+#[cfg(test)]
+use hir_def::EnumVariantId;
+
+#[cfg(test)]
+use crate::{
+    primitive::{IntTy, UintTy},
+    Scalar,
+};
+
+#[cfg(test)]
+use super::deconstruct_pat::IntRange;
 
 use self::{helper::Captures, ArmType::*, Usefulness::*};
 
+/// How many witnesses of non-exhaustiveness the checks look for by default. Diagnostics only show
+/// the first few of them, and count the others up to this limit.
+const MAX_WITNESSES: usize = 100;
+
+/// How many times `is_useful` recurses for a row by default, which is way more than the nesting
+/// of hand-written patterns, but keeps generated ones from taking forever to check.
+const MAX_DEPTH: usize = 256;
+
+/// The target information and the limits that the checks of the matches of one body work with.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct MatchCheckConfig {
+    /// The width of `isize` and `usize` in bits, from the `target_pointer_width` cfg of the crate.
+    /// Matches on these types can only be exhaustive without a wildcard arm when it is known.
+    pub(crate) target_pointer_width: Option<u32>,
+    /// How many witnesses of non-exhaustiveness to look for at most, for diagnostics that only
+    /// show the first few anyway.
+    pub(crate) max_witnesses: Option<usize>,
+    /// How many times `is_useful` may recurse for a row, which happens for each column and each
+    /// level of nesting of the patterns. Past the limit, the rest of the row is assumed to be
+    /// reachable and to leave nothing uncovered, so that no diagnostic is wrong, and the report
+    /// has `reached_max_depth` set.
+    pub(crate) max_depth: Option<usize>,
+}
+
+/// The context shared by all the checks of the matches of one body. Build it with
+/// [`MatchCheckCtx::new`].
 pub(crate) struct MatchCheckCtx<'a, 'p> {
     pub(crate) module: ModuleId,
     pub(crate) body: DefWithBodyId,
     pub(crate) db: &'a dyn HirDatabase,
     /// Lowered patterns from arms plus generated by the check.
     pub(crate) pattern_arena: &'p Arena<DeconstructedPat<'p>>,
+    /// Cache for `Fields::wildcards`, which is called a lot with the same arguments.
+    pub(super) wildcards_cache: RefCell<FxHashMap<(Ty, Constructor), Fields<'p>>>,
     exhaustive_patterns: bool,
+    /// The target information and the limits of the checks.
+    pub(crate) config: MatchCheckConfig,
+    /// How deep `is_useful` currently recurses.
+    depth: Cell<usize>,
+    /// Whether `is_useful` gave up on a row because it reached `max_depth`.
+    reached_max_depth: Cell<bool>,
+    /// The instrumentation collected so far, if enabled with `collect_stats`.
+    #[cfg(test)]
+    stats: RefCell<Option<UsefulnessStats>>,
 }
 
 impl<'a, 'p> MatchCheckCtx<'a, 'p> {
+    /// Creates the context for checking the matches of `body`, as seen from `module`. This is the
+    /// one place that sets up the caches and reads the unstable features and the target of the
+    /// crate, so new context state should be initialized here.
+    ///
+    /// ```ignore
+    /// let pattern_arena = Arena::new();
+    /// let cx = MatchCheckCtx::new(owner.module(db.upcast()), owner, db, &pattern_arena);
+    /// let report = compute_match_usefulness(&cx, &arms, &scrut_ty);
+    /// ```
     pub(crate) fn new(
         module: ModuleId,
         body: DefWithBodyId,
         db: &'a dyn HirDatabase,
         pattern_arena: &'p Arena<DeconstructedPat<'p>>,
     ) -> Self {
-        let def_map = db.crate_def_map(module.krate());
+        let krate = module.krate();
+        let def_map = db.crate_def_map(krate);
         let exhaustive_patterns = def_map.is_unstable_feature_enabled("exhaustive_patterns");
-        Self { module, body, db, pattern_arena, exhaustive_patterns }
+        let target_pointer_width = db.crate_graph()[krate]
+            .cfg_options
+            .get_cfg_values("target_pointer_width")
+            .filter_map(|width| width.parse().ok())
+            .find(|width| matches!(width, 16 | 32 | 64));
+        let config = MatchCheckConfig {
+            target_pointer_width,
+            max_witnesses: Some(MAX_WITNESSES),
+            max_depth: Some(MAX_DEPTH),
+        };
+        Self {
+            module,
+            body,
+            db,
+            pattern_arena,
+            wildcards_cache: RefCell::default(),
+            exhaustive_patterns,
+            config,
+            depth: Cell::new(0),
+            reached_max_depth: Cell::new(false),
+            #[cfg(test)]
+            stats: RefCell::new(None),
+        }
     }
 
     pub(super) fn is_uninhabited(&self, ty: &Ty) -> bool {
@@ -329,6 +425,20 @@ pub(super) fn is_foreign_non_exhaustive_enum(&self, ty: &Ty) -> bool {
     pub(super) fn feature_exhaustive_patterns(&self) -> bool {
         self.exhaustive_patterns
     }
+
+    /// Records how the columns of the checked matches split into constructors, to find out why a
+    /// match is slow to check. Get the records with `take_stats`.
+    #[cfg(test)]
+    pub(crate) fn collect_stats(&self) {
+        *self.stats.borrow_mut() = Some(UsefulnessStats::default());
+    }
+
+    /// Returns the records collected since the last call, or `None` if `collect_stats` wasn't
+    /// called.
+    #[cfg(test)]
+    pub(crate) fn take_stats(&self) -> Option<UsefulnessStats> {
+        self.stats.borrow_mut().as_mut().map(mem::take)
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -345,6 +455,10 @@ pub(super) struct PatCtxt<'a, 'p> {
 
 /// A row of a matrix. Rows of len 1 are very common, which is why `SmallVec[_; 2]`
 /// works well.
+///
+/// The constructor of the head is not cached here: it is read from the head pattern, which
+/// computes it once when it is deconstructed. A row whose patterns are replaced thus never sees a
+/// stale constructor, and rows can be reused without resetting anything.
 #[derive(Clone)]
 pub(super) struct PatStack<'p> {
     pats: SmallVec<[&'p DeconstructedPat<'p>; 2]>,
@@ -355,7 +469,7 @@ fn from_pattern(pat: &'p DeconstructedPat<'p>) -> Self {
         Self::from_vec(smallvec![pat])
     }
 
-    fn from_vec(vec: SmallVec<[&'p DeconstructedPat<'p>; 2]>) -> Self {
+    pub(super) fn from_vec(vec: SmallVec<[&'p DeconstructedPat<'p>; 2]>) -> Self {
         PatStack { pats: vec }
     }
 
@@ -371,6 +485,10 @@ fn head(&self) -> &'p DeconstructedPat<'p> {
         self.pats[0]
     }
 
+    fn iter(&self) -> impl Iterator<Item = &'p DeconstructedPat<'p>> + Captures<'_> {
+        self.pats.iter().copied()
+    }
+
     // Recursively expand the first pattern into its subpatterns. Only useful if the pattern is an
     // or-pattern. Panics if `self` is empty.
     fn expand_or_pat(&self) -> impl Iterator<Item = PatStack<'p>> + Captures<'_> {
@@ -396,34 +514,90 @@ fn pop_head_constructor(&self, cx: &MatchCheckCtx<'_, 'p>, ctor: &Constructor) -
     }
 }
 
+#[cfg(feature = "trace")]
+impl fmt::Debug for PatStack<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "+")?;
+        for pat in self.iter() {
+            write!(f, " {:?} +", pat)?;
+        }
+        Ok(())
+    }
+}
+
 /// A 2D matrix.
 #[derive(Clone)]
 pub(super) struct Matrix<'p> {
     patterns: Vec<PatStack<'p>>,
 }
 
+/// Prints the matrix one row per line, like
+///
+/// ```text
+/// + IntRange(IntRange { range: 1..=1 }) + _ +
+/// + _ + IntRange(IntRange { range: 0..=0 }) +
+/// ```
+#[cfg(feature = "trace")]
+impl fmt::Debug for Matrix<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f)?;
+        for row in &self.patterns {
+            writeln!(f, "{:?}", row)?;
+        }
+        Ok(())
+    }
+}
+
 impl<'p> Matrix<'p> {
-    fn empty() -> Self {
+    pub(super) fn empty() -> Self {
         Matrix { patterns: vec![] }
     }
 
+    /// Whether the matrix has no rows.
+    fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
     /// Number of columns of this matrix. `None` is the matrix is empty.
-    pub(super) fn _column_count(&self) -> Option<usize> {
+    pub(super) fn column_count(&self) -> Option<usize> {
         self.patterns.get(0).map(|r| r.len())
     }
 
     /// Pushes a new row to the matrix. If the row starts with an or-pattern, this recursively
     /// expands it.
-    fn push(&mut self, row: PatStack<'p>) {
+    ///
+    /// A row of another length than the rows already in the matrix is the result of a bug in
+    /// constructor arities: it is logged and skipped, so that the failure points at the row
+    /// instead of panicking later in `is_useful`. Likewise, a row headed by the synthetic
+    /// `Missing` constructor means a witness leaked into the matrix.
+    pub(super) fn push(&mut self, row: PatStack<'p>) {
+        if let Err(err) = self.try_push(row) {
+            never!("pushed an invalid row to the matrix: {:?}", err);
+        }
+    }
+
+    /// Like `push`, but returns why an invalid row was skipped instead of logging it.
+    pub(super) fn try_push(&mut self, row: PatStack<'p>) -> Result<(), InvalidRow> {
+        if let Some(count) = self.column_count() {
+            if row.len() != count {
+                return Err(InvalidRow::ColumnCount { row: row.len(), matrix: count });
+            }
+        }
+        if !row.is_empty() && row.head().ctor().is_missing() {
+            return Err(InvalidRow::MissingCtor);
+        }
         if !row.is_empty() && row.head().is_or_pat() {
             self.patterns.extend(row.expand_or_pat());
         } else {
             self.patterns.push(row);
         }
+        Ok(())
     }
 
     /// Iterate over the first component of each row
-    fn heads(&self) -> impl Iterator<Item = &'p DeconstructedPat<'p>> + Clone + Captures<'_> {
+    pub(super) fn heads(
+        &self,
+    ) -> impl Iterator<Item = &'p DeconstructedPat<'p>> + Clone + Captures<'_> {
         self.patterns.iter().map(|r| r.head())
     }
 
@@ -440,6 +614,15 @@ fn specialize_constructor(&self, pcx: PatCtxt<'_, 'p>, ctor: &Constructor) -> Ma
     }
 }
 
+/// Why [`Matrix::try_push`] skipped a row.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum InvalidRow {
+    /// The row doesn't have as many columns as the rows already in the matrix.
+    ColumnCount { row: usize, matrix: usize },
+    /// The row is headed by the synthetic `Missing` constructor, which only witnesses use.
+    MissingCtor,
+}
+
 /// This carries the results of computing usefulness, as described at the top of the file. When
 /// checking usefulness of a match branch, we use the `NoWitnesses` variant, which also keeps track
 /// of potential unreachable sub-patterns (in the presence of or-patterns). When checking
@@ -476,8 +659,29 @@ fn is_useful(&self) -> bool {
         }
     }
 
-    /// Combine usefulnesses from two branches. This is an associative operation.
-    fn extend(&mut self, other: Self) {
+    /// The complete witnesses that were found, without the malformed ones. Checking an arm doesn't
+    /// look for witnesses, so there are none then.
+    fn into_witnesses(self) -> Vec<DeconstructedPat<'p>> {
+        match self {
+            WithWitnesses(witnesses) => {
+                witnesses.into_iter().filter_map(Witness::single_pattern).collect()
+            }
+            NoWitnesses { .. } => Vec::new(),
+        }
+    }
+
+    /// Whether `max_witnesses` witnesses were found already, so that there's no need to look for
+    /// more.
+    fn has_max_witnesses(&self, max_witnesses: Option<usize>) -> bool {
+        match (self, max_witnesses) {
+            (WithWitnesses(witnesses), Some(max)) => witnesses.len() >= max,
+            _ => false,
+        }
+    }
+
+    /// Combine usefulnesses from two branches. This is an associative operation. Witnesses past
+    /// `max_witnesses` are dropped.
+    fn extend(&mut self, other: Self, max_witnesses: Option<usize>) {
         match (&mut *self, other) {
             (WithWitnesses(_), WithWitnesses(o)) if o.is_empty() => {}
             (WithWitnesses(s), WithWitnesses(o)) if s.is_empty() => *self = WithWitnesses(o),
@@ -487,6 +691,9 @@ fn extend(&mut self, other: Self) {
             }
             _ => unreachable!(),
         }
+        if let (WithWitnesses(witnesses), Some(max)) = (&mut *self, max_witnesses) {
+            witnesses.truncate(max);
+        }
     }
 
     /// After calculating usefulness after a specialization, call this to reconstruct a usefulness
@@ -607,13 +814,26 @@ enum ArmType {
 ///     `Witness(vec![Pair(Some(_), true)])`
 ///
 /// The final `Pair(Some(_), true)` is then the resulting witness.
-pub(crate) struct Witness<'p>(Vec<DeconstructedPat<'p>>);
+pub(crate) struct Witness<'p>(pub(super) Vec<DeconstructedPat<'p>>);
 
 impl<'p> Witness<'p> {
-    /// Asserts that the witness contains a single pattern, and returns it.
-    fn single_pattern(self) -> DeconstructedPat<'p> {
-        assert_eq!(self.0.len(), 1);
-        self.0.into_iter().next().unwrap()
+    /// Returns the single pattern of a complete witness. A witness of another length is the result
+    /// of a bug in constructor arities: it is logged and `None` is returned, so that the rest of
+    /// the analysis can go on.
+    pub(super) fn single_pattern(self) -> Option<DeconstructedPat<'p>> {
+        if self.0.len() != 1 {
+            let ctors: Vec<_> = self.0.iter().map(DeconstructedPat::ctor).collect();
+            never!("witness should have a single pattern, got {:?}", ctors);
+        }
+        self.into_single_pattern()
+    }
+
+    /// Like `single_pattern`, but doesn't log a witness of another length.
+    pub(super) fn into_single_pattern(self) -> Option<DeconstructedPat<'p>> {
+        if self.0.len() != 1 {
+            return None;
+        }
+        self.0.into_iter().next()
     }
 
     /// Constructs a partial witness for a pattern given a list of
@@ -629,10 +849,34 @@ fn single_pattern(self) -> DeconstructedPat<'p> {
     ///
     /// left_ty: struct X { a: (bool, &'static str), b: usize}
     /// pats: [(false, "foo"), 42]  => X { a: (false, "foo"), b: 42 }
-    fn apply_constructor(mut self, pcx: PatCtxt<'_, 'p>, ctor: &Constructor) -> Self {
+    ///
+    /// A witness with fewer patterns than the arity of `ctor` is the result of a bug in
+    /// constructor arities: it is logged and returned unchanged.
+    pub(super) fn apply_constructor(self, pcx: PatCtxt<'_, 'p>, ctor: &Constructor) -> Self {
+        let len = self.0.len();
+        self.try_apply_constructor(pcx, ctor).unwrap_or_else(|witness| {
+            never!(
+                "applied a constructor of arity {} to a witness of {} patterns",
+                ctor.arity(pcx),
+                len
+            );
+            witness
+        })
+    }
+
+    /// Like `apply_constructor`, but returns a witness with fewer patterns than the arity of
+    /// `ctor` unchanged as the error instead of logging it.
+    pub(super) fn try_apply_constructor(
+        mut self,
+        pcx: PatCtxt<'_, 'p>,
+        ctor: &Constructor,
+    ) -> Result<Self, Self> {
         let pat = {
             let len = self.0.len();
             let arity = ctor.arity(pcx);
+            if arity > len {
+                return Err(self);
+            }
             let pats = self.0.drain((len - arity)..).rev();
             let fields = Fields::from_iter(pcx.cx, pats);
             DeconstructedPat::new(ctor.clone(), fields, pcx.ty.clone())
@@ -640,7 +884,7 @@ fn apply_constructor(mut self, pcx: PatCtxt<'_, 'p>, ctor: &Constructor) -> Self
 
         self.0.push(pat);
 
-        self
+        Ok(self)
     }
 }
 
@@ -648,7 +892,8 @@ fn apply_constructor(mut self, pcx: PatCtxt<'_, 'p>, ctor: &Constructor) -> Self
 /// The algorithm from the paper has been modified to correctly handle empty
 /// types. The changes are:
 ///   (0) We don't exit early if the pattern matrix has zero rows. We just
-///       continue to recurse over columns.
+///       continue to recurse over columns, unless we can tell the answer
+///       from the types of `v` alone (see below).
 ///   (1) all_constructors will only return constructors that are statically
 ///       possible. E.g., it will only return `Ok` for `Result<T, !>`.
 ///
@@ -674,15 +919,10 @@ fn is_useful<'p>(
     is_under_guard: bool,
     is_top_level: bool,
 ) -> Usefulness<'p> {
-    let Matrix { patterns: rows, .. } = matrix;
-
     // The base case. We are pattern-matching on () and the return value is
     // based on whether our matrix has a row or not.
-    // NOTE: This could potentially be optimized by checking rows.is_empty()
-    // first and then, if v is non-empty, the return value is based on whether
-    // the type of the tuple we're checking is inhabited or not.
     if v.is_empty() {
-        let ret = if rows.is_empty() {
+        let ret = if matrix.is_empty() {
             Usefulness::new_useful(witness_preference)
         } else {
             Usefulness::new_not_useful(witness_preference)
@@ -690,7 +930,45 @@ fn is_useful<'p>(
         return ret;
     }
 
-    debug_assert!(rows.iter().all(|r| r.len() == v.len()));
+    // If the matrix has no rows, `v` is useful iff some value matches it, which only depends on
+    // the types of `v`. We still need to specialize when we want witnesses, or when `v` contains
+    // or-patterns since the reachability of an alternative depends on the alternatives before
+    // it. At the top level, empty types are handled specially by `SplitWildcard::new` so we
+    // don't take the shortcut there either.
+    if matrix.is_empty()
+        && !is_top_level
+        && !matches!(witness_preference, FakeExtraWildcard)
+        && !v.iter().any(DeconstructedPat::contains_or_pat)
+    {
+        let useful = v.iter().all(|pat| pat.is_inhabited(cx));
+        if useful && matches!(witness_preference, RealArm) {
+            v.iter().for_each(DeconstructedPat::set_reachable_recursively);
+        }
+        return NoWitnesses { useful };
+    }
+
+    if let Some(count) = matrix.column_count() {
+        if never!(
+            count != v.len(),
+            "checked a row of {} columns against a matrix of {} columns",
+            v.len(),
+            count
+        ) {
+            return Usefulness::new_not_useful(witness_preference);
+        }
+    }
+
+    if cx.config.max_depth.map_or(false, |max| cx.depth.get() >= max) {
+        cx.reached_max_depth.set(true);
+        return match witness_preference {
+            RealArm => {
+                v.iter().for_each(DeconstructedPat::set_reachable_recursively);
+                NoWitnesses { useful: true }
+            }
+            FakeExtraWildcard => Usefulness::new_not_useful(witness_preference),
+        };
+    }
+    cx.depth.set(cx.depth.get() + 1);
 
     let ty = v.head().ty();
     let is_non_exhaustive = cx.is_foreign_non_exhaustive_enum(ty);
@@ -703,7 +981,7 @@ fn is_useful<'p>(
         let mut matrix = matrix.clone();
         for v in v.expand_or_pat() {
             let usefulness = is_useful(cx, &matrix, &v, witness_preference, is_under_guard, false);
-            ret.extend(usefulness);
+            ret.extend(usefulness, cx.config.max_witnesses);
             // If pattern has a guard don't add it to the matrix.
             if !is_under_guard {
                 // We push the already-seen patterns into the matrix in order to detect redundant
@@ -718,6 +996,16 @@ fn is_useful<'p>(
 
         // We split the head constructor of `v`.
         let split_ctors = v_ctor.split(pcx, matrix.heads().map(DeconstructedPat::ctor));
+        #[cfg(test)]
+        if let Some(stats) = &mut *cx.stats.borrow_mut() {
+            let distinct_ctors: FxHashSet<_> =
+                matrix.heads().map(DeconstructedPat::ctor).filter(|c| !c.is_wildcard()).collect();
+            stats.splits.push(ColumnSplit {
+                ty: ty.clone(),
+                distinct_ctors: distinct_ctors.len(),
+                split_ctors: split_ctors.len(),
+            });
+        }
         // For each constructor, we compute whether there's a value that starts with it that would
         // witness the usefulness of `v`.
         let start_matrix = matrix;
@@ -731,17 +1019,71 @@ fn is_useful<'p>(
 
             // FIXME: implement `non_exhaustive_omitted_patterns` lint
 
-            ret.extend(usefulness);
+            ret.extend(usefulness, cx.config.max_witnesses);
+            if ret.has_max_witnesses(cx.config.max_witnesses) {
+                break;
+            }
         }
     };
 
-    if ret.is_useful() {
+    // Only the patterns of real arms track their reachability, `FakeExtraWildcard` may be checking
+    // patterns that are shared with an arm without being that arm.
+    if ret.is_useful() && matches!(witness_preference, RealArm) {
         v.head().set_reachable();
     }
 
+    cx.depth.set(cx.depth.get() - 1);
     ret
 }
 
+/// Checks `pat` against no rows below the top level, first like `is_useful` does for an arm, which
+/// only looks at the types, then by splitting its constructors like it does for witnesses. Tests
+/// compare both to make sure the shortcut doesn't change the result.
+#[cfg(test)]
+pub(super) fn usefulness_against_no_rows<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    pat: &'p DeconstructedPat<'p>,
+) -> (bool, bool) {
+    let v = PatStack::from_pattern(pat);
+    let shortcut = is_useful(cx, &Matrix::empty(), &v, RealArm, false, false).is_useful();
+    let general = is_useful(cx, &Matrix::empty(), &v, FakeExtraWildcard, false, false).is_useful();
+    (shortcut, general)
+}
+
+/// Instrumentation of the usefulness check, see [`MatchCheckCtx::collect_stats`].
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct UsefulnessStats {
+    /// Every time a column was split into constructors, in order.
+    pub(crate) splits: Vec<ColumnSplit>,
+}
+
+#[cfg(test)]
+impl UsefulnessStats {
+    /// For each number of distinct constructors found in a column, how many times a column with
+    /// that many was split. Splits of columns with many constructors are the slow ones.
+    pub(crate) fn distinct_ctors_histogram(&self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for split in &self.splits {
+            *histogram.entry(split.distinct_ctors).or_default() += 1;
+        }
+        histogram
+    }
+}
+
+/// One split of a column into constructors, recorded in [`UsefulnessStats`].
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct ColumnSplit {
+    /// The type of the column.
+    pub(crate) ty: Ty,
+    /// The number of distinct constructors the rows of the matrix start with, wildcards aside.
+    pub(crate) distinct_ctors: usize,
+    /// The number of constructors the column was split into, which is how many times the check
+    /// recursed for it.
+    pub(crate) split_ctors: usize,
+}
+
 /// The arm of a match expression.
 #[derive(Clone, Copy)]
 pub(crate) struct MatchArm<'p> {
@@ -749,18 +1091,37 @@ pub(crate) struct MatchArm<'p> {
     pub(crate) has_guard: bool,
 }
 
+impl<'p> MatchArm<'p> {
+    /// The or-pattern branches that were found unreachable despite the overall arm being
+    /// reachable. Only meaningful once the arm went through `compute_match_usefulness`, which
+    /// leaves the marks on the lowered pattern. Empty for an unreachable arm, whose whole pattern
+    /// is unreachable instead.
+    #[cfg(test)]
+    pub(crate) fn unreachable_subpatterns(&self, cx: &MatchCheckCtx<'_, 'p>) -> Vec<Pat> {
+        if !self.pat.is_reachable() {
+            return Vec::new();
+        }
+        self.pat.unreachable_subpatterns().into_iter().map(|p| p.to_pat(cx)).collect()
+    }
+}
+
 /// Indicates whether or not a given arm is reachable.
 #[derive(Clone, Debug)]
 pub(crate) enum Reachability {
-    /// The arm is reachable. This additionally carries a set of or-pattern branches that have been
-    /// found to be unreachable despite the overall arm being reachable. Used only in the presence
-    /// of or-patterns, otherwise it stays empty.
-    // FIXME: store ureachable subpattern IDs
+    /// The arm is reachable. Or-pattern branches that are unreachable despite the overall arm
+    /// being reachable are only computed on demand, see `MatchArm::unreachable_subpatterns`.
     Reachable,
     /// The arm is unreachable.
     Unreachable,
 }
 
+impl Reachability {
+    /// Whether the arm is reachable, even if some of its or-pattern branches aren't.
+    pub(crate) fn is_reachable(&self) -> bool {
+        matches!(self, Reachability::Reachable)
+    }
+}
+
 /// The output of checking a match for exhaustiveness and arm reachability.
 pub(crate) struct UsefulnessReport<'p> {
     /// For each arm of the input, whether that arm is reachable after the arms above it.
@@ -768,6 +1129,240 @@ pub(crate) struct UsefulnessReport<'p> {
     /// If the match is exhaustive, this is empty. If not, this contains witnesses for the lack of
     /// exhaustiveness.
     pub(crate) non_exhaustiveness_witnesses: Vec<DeconstructedPat<'p>>,
+    /// Whether the check gave up on some rows at the depth set by
+    /// [`MatchCheckConfig::max_depth`], in which case the arms may not all be reachable and the
+    /// witnesses may not be all the values the match leaves uncovered.
+    pub(crate) reached_max_depth: bool,
+}
+
+impl<'p> UsefulnessReport<'p> {
+    /// Indices of the arms that have a guard but whose pattern is unreachable, so that the guard
+    /// is never even evaluated.
+    #[cfg(test)]
+    pub(crate) fn guard_unreachable(&self) -> Vec<usize> {
+        let arms = self._arm_usefulness.iter().enumerate();
+        arms.filter(|(_, (arm, reachability))| arm.has_guard && !reachability.is_reachable())
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Passes the witnesses of non-exhaustiveness to `sink`, which builds the representation
+    /// of them its consumer needs. Each witness is walked once, its constructors first.
+    #[cfg(test)]
+    pub(crate) fn feed_witnesses<S: WitnessSink<'p>>(
+        &self,
+        cx: &MatchCheckCtx<'_, 'p>,
+        mut sink: S,
+    ) -> S::Output {
+        fn push_constructors<'p>(
+            cx: &MatchCheckCtx<'_, 'p>,
+            sink: &mut impl WitnessSink<'p>,
+            pat: &DeconstructedPat<'p>,
+        ) {
+            sink.push_constructor(cx, pat);
+            pat.iter_fields().for_each(|field| push_constructors(cx, sink, field));
+        }
+
+        for witness in &self.non_exhaustiveness_witnesses {
+            push_constructors(cx, &mut sink, witness);
+            sink.push_witness(cx, witness);
+        }
+        sink.finish()
+    }
+
+    /// Combines the reports of matches on the same scrutinee type that together act as one match,
+    /// like the matches a macro splits one match into, with the arms of `self` coming first. The
+    /// arms of both are listed in order, and a value is only left uncovered if neither match
+    /// covers it: the witnesses are those of `self` narrowed down to what the arms of `other`
+    /// don't cover.
+    #[cfg(test)]
+    pub(crate) fn merge(self, cx: &MatchCheckCtx<'_, 'p>, other: Self) -> Self {
+        // Narrowing down the witnesses can give up at the maximum depth too. The flag of `cx` is
+        // only borrowed for that, as it belongs to the report of the last checked match.
+        let reached_max_depth_before = cx.reached_max_depth.replace(false);
+        let mut matrix = Matrix::empty();
+        for (arm, _) in &other._arm_usefulness {
+            if !arm.has_guard {
+                matrix.push(PatStack::from_pattern(arm.pat));
+            }
+        }
+        let witnesses = self
+            .non_exhaustiveness_witnesses
+            .into_iter()
+            .flat_map(|witness| {
+                let v = PatStack::from_pattern(cx.pattern_arena.alloc(witness));
+                is_useful(cx, &matrix, &v, FakeExtraWildcard, false, true).into_witnesses()
+            })
+            .collect();
+        let non_exhaustiveness_witnesses = dedup_witnesses(cx, witnesses);
+        let reached_max_depth = self.reached_max_depth
+            || other.reached_max_depth
+            || cx.reached_max_depth.replace(reached_max_depth_before);
+
+        let mut arm_usefulness = self._arm_usefulness;
+        arm_usefulness.extend(other._arm_usefulness);
+        UsefulnessReport {
+            _arm_usefulness: arm_usefulness,
+            non_exhaustiveness_witnesses,
+            reached_max_depth,
+        }
+    }
+
+    /// The variants of the witnesses, if they are all variants of the same enum with only
+    /// wildcards inside, like `None` and `Some(_)`, so that they can be reported together. Empty
+    /// otherwise.
+    #[cfg(test)]
+    pub(crate) fn missing_variants(&self) -> Vec<EnumVariantId> {
+        let variants = self
+            .non_exhaustiveness_witnesses
+            .iter()
+            .map(|witness| {
+                let variant = witness.ctor().as_variant()?;
+                witness.iter_fields().all(|field| field.ctor().is_wildcard()).then(|| variant)
+            })
+            .collect::<Option<Vec<_>>>();
+        match variants {
+            Some(variants) if variants.windows(2).all(|pair| pair[0].parent == pair[1].parent) => {
+                variants
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// How many arms at least are missing for the match to be exhaustive: the number of distinct
+    /// constructors the witnesses start with. There can be more witnesses, like `Some(0)` and
+    /// `Some(1)` which one `Some(_)` arm covers.
+    #[cfg(test)]
+    pub(crate) fn min_arms_to_complete(&self) -> usize {
+        let ctors = self.non_exhaustiveness_witnesses.iter().map(DeconstructedPat::ctor);
+        ctors.collect::<FxHashSet<_>>().len()
+    }
+
+    /// Why the match isn't exhaustive, if it isn't.
+    #[cfg(test)]
+    pub(crate) fn non_exhaustive_reason(
+        &self,
+        cx: &MatchCheckCtx<'_, 'p>,
+    ) -> Option<NonExhaustiveReason> {
+        let arms: Vec<_> = self._arm_usefulness.iter().map(|(arm, _)| *arm).collect();
+        NonExhaustiveReason::of_witnesses(cx, &arms, &self.non_exhaustiveness_witnesses)
+    }
+
+    /// Finds the reachable range arms that partly overlap the range of an earlier arm, like
+    /// `100..=255` after `0..=200`. Such an arm still matches new values, so it isn't unreachable,
+    /// but the overlap is likely a mistake. Ranges containing one another aren't reported, as
+    /// handling some values before a more general arm is common.
+    ///
+    /// Returns the index of the earlier arm, the index of the later one, and the values they both
+    /// match. Arms are identified by their index in the match, like in `MatchCheckResult`, as the
+    /// lowered patterns don't keep the `PatId` they come from.
+    #[cfg(test)]
+    pub(crate) fn range_overlaps(&self) -> Vec<(usize, usize, (i128, i128))> {
+        let arm_range = |arm: &MatchArm<'_>| match (arm.pat.ctor(), arm.pat.ty().kind(Interner)) {
+            (Constructor::IntRange(range), &TyKind::Scalar(scalar_ty)) => Some((range, scalar_ty)),
+            _ => None,
+        };
+        let arms = &self._arm_usefulness;
+        let mut overlaps = Vec::new();
+        for (idx, (arm, reachability)) in arms.iter().enumerate() {
+            let (range, scalar_ty) = match (reachability, arm_range(arm)) {
+                (Reachability::Reachable, Some(range)) => range,
+                _ => continue,
+            };
+            for (prev_idx, (prev_arm, _)) in arms[..idx].iter().enumerate() {
+                if let Some((prev_range, _)) = arm_range(prev_arm) {
+                    if let Some(overlap) = prev_range.partial_overlap(range, scalar_ty) {
+                        overlaps.push((prev_idx, idx, overlap));
+                    }
+                }
+            }
+        }
+        overlaps
+    }
+}
+
+/// The cause of the non-exhaustiveness of a match, for diagnostics to explain it.
+#[cfg(test)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum NonExhaustiveReason {
+    /// Some variants or values are not covered by any arm.
+    MissingVariants,
+    /// A `#[non_exhaustive]` enum of another crate always needs a wildcard arm, even if all of its
+    /// variants are covered.
+    ForeignNonExhaustive,
+    /// Without the `exhaustive_patterns` feature, an empty type that isn't the type of the
+    /// scrutinee itself is treated as if it had values.
+    UninhabitedNotFeatureGated,
+    /// The values of the type can't be listed, so only a wildcard covers them.
+    InfiniteType(InfiniteType),
+    /// We can't look inside the type, like a type parameter or a trait object, so only a wildcard
+    /// covers it.
+    UnknownConstructors,
+    /// Every arm has a guard, and all of them may fail, so an arm without a guard is needed.
+    AllArmsGuarded,
+}
+
+/// The types whose values can't be listed, see [`NonExhaustiveReason::InfiniteType`].
+#[cfg(test)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum InfiniteType {
+    Str,
+    /// `isize` and `usize`, whose range depends on the target.
+    Int,
+    Float,
+}
+
+#[cfg(test)]
+impl NonExhaustiveReason {
+    /// Finds the reason from the wildcards of the witnesses: they stand for the values that
+    /// `SplitWildcard` couldn't list. If no wildcard has a special reason, the witnesses are plain
+    /// missing constructors. A match whose arms all have guards is reported as such first, as no
+    /// pattern can make it exhaustive.
+    fn of_witnesses(
+        cx: &MatchCheckCtx<'_, '_>,
+        arms: &[MatchArm<'_>],
+        witnesses: &[DeconstructedPat<'_>],
+    ) -> Option<Self> {
+        if witnesses.is_empty() {
+            return None;
+        }
+        if !arms.is_empty() && arms.iter().all(|arm| arm.has_guard) {
+            return Some(NonExhaustiveReason::AllArmsGuarded);
+        }
+        let reason = witnesses.iter().find_map(|witness| Self::of_witness(cx, witness));
+        Some(reason.unwrap_or(NonExhaustiveReason::MissingVariants))
+    }
+
+    fn of_witness(cx: &MatchCheckCtx<'_, '_>, witness: &DeconstructedPat<'_>) -> Option<Self> {
+        let ctor = witness.ctor();
+        if !(ctor.is_wildcard() || ctor.is_non_exhaustive()) {
+            return witness.iter_fields().find_map(|field| Self::of_witness(cx, field));
+        }
+        let ty = witness.ty();
+        let reason = match ty.kind(Interner) {
+            _ if cx.is_foreign_non_exhaustive_enum(ty) => NonExhaustiveReason::ForeignNonExhaustive,
+            TyKind::Str => NonExhaustiveReason::InfiniteType(InfiniteType::Str),
+            TyKind::Scalar(Scalar::Int(IntTy::Isize) | Scalar::Uint(UintTy::Usize)) => {
+                NonExhaustiveReason::InfiniteType(InfiniteType::Int)
+            }
+            TyKind::Scalar(Scalar::Float(_)) => {
+                NonExhaustiveReason::InfiniteType(InfiniteType::Float)
+            }
+            TyKind::Placeholder(..)
+            | TyKind::Alias(_)
+            | TyKind::AssociatedType(..)
+            | TyKind::OpaqueType(..)
+            | TyKind::Dyn(_)
+            | TyKind::Foreign(_) => NonExhaustiveReason::UnknownConstructors,
+            _ if is_ty_uninhabited_from(ty, cx.module, cx.db) => {
+                NonExhaustiveReason::UninhabitedNotFeatureGated
+            }
+            // Other types whose constructors we don't list, like arrays or function pointers.
+            _ if ctor.is_non_exhaustive() => NonExhaustiveReason::UnknownConstructors,
+            _ => return None,
+        };
+        Some(reason)
+    }
 }
 
 /// The entrypoint for the usefulness algorithm. Computes whether a match is exhaustive and which
@@ -780,8 +1375,9 @@ pub(crate) fn compute_match_usefulness<'p>(
     arms: &[MatchArm<'p>],
     scrut_ty: &Ty,
 ) -> UsefulnessReport<'p> {
+    cx.reached_max_depth.set(false);
     let mut matrix = Matrix::empty();
-    let arm_usefulness = arms
+    let arm_usefulness: Vec<_> = arms
         .iter()
         .copied()
         .map(|arm| {
@@ -799,14 +1395,445 @@ pub(crate) fn compute_match_usefulness<'p>(
         })
         .collect();
 
-    let wild_pattern = cx.pattern_arena.alloc(DeconstructedPat::wildcard(scrut_ty.clone()));
+    let scrut_ty = normalize_pat_ty(cx.db, cx.body, scrut_ty);
+    let non_exhaustiveness_witnesses = if has_single_inhabitant(cx, &scrut_ty) {
+        // Any arm matches the one value, so there's no need to split the type into constructors
+        // and apply them back to the witnesses.
+        if matrix.is_empty() {
+            let pcx = PatCtxt { cx, ty: &scrut_ty, is_top_level: true, is_non_exhaustive: false };
+            vec![DeconstructedPat::wild_from_ctor(pcx, Constructor::Single)]
+        } else {
+            Vec::new()
+        }
+    } else {
+        compute_witnesses(cx, &matrix, scrut_ty)
+    };
+    UsefulnessReport {
+        _arm_usefulness: arm_usefulness,
+        non_exhaustiveness_witnesses,
+        reached_max_depth: cx.reached_max_depth.get(),
+    }
+}
+
+/// Computes the witnesses of non-exhaustiveness of a match on `scrut_ty` whose arms without a guard
+/// are the rows of `matrix`.
+pub(super) fn compute_witnesses<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    matrix: &Matrix<'p>,
+    scrut_ty: Ty,
+) -> Vec<DeconstructedPat<'p>> {
+    let wild_pattern = cx.pattern_arena.alloc(DeconstructedPat::wildcard(scrut_ty));
     let v = PatStack::from_pattern(wild_pattern);
-    let usefulness = is_useful(cx, &matrix, &v, FakeExtraWildcard, false, true);
-    let non_exhaustiveness_witnesses = match usefulness {
-        WithWitnesses(pats) => pats.into_iter().map(Witness::single_pattern).collect(),
-        NoWitnesses { .. } => panic!("bug"),
+    let witnesses = is_useful(cx, matrix, &v, FakeExtraWildcard, false, true).into_witnesses();
+    dedup_witnesses(cx, witnesses)
+}
+
+/// Whether `ty` has exactly one value, like `()` or a unit struct, which every pattern of the type
+/// matches.
+fn has_single_inhabitant(cx: &MatchCheckCtx<'_, '_>, ty: &Ty) -> bool {
+    has_single_inhabitant_(cx, ty, &mut FxHashSet::default())
+}
+
+/// `visited` holds the structs whose fields are being checked. A struct that contains itself, like
+/// `struct A(A);`, doesn't compile, but it can still be typed, and is taken as having other values.
+fn has_single_inhabitant_(
+    cx: &MatchCheckCtx<'_, '_>,
+    ty: &Ty,
+    visited: &mut FxHashSet<StructId>,
+) -> bool {
+    match ty.kind(Interner) {
+        TyKind::Tuple(_, substs) => substs
+            .iter(Interner)
+            .filter_map(|arg| arg.ty(Interner))
+            .all(|ty| has_single_inhabitant_(cx, ty, visited)),
+        _ => match ty.as_adt() {
+            Some((AdtId::StructId(id), substs)) => {
+                if !visited.insert(id) {
+                    return false;
+                }
+                let single = cx.db.field_types(id.into()).iter().all(|(_, field_ty)| {
+                    let field_ty = field_ty.clone().substitute(Interner, substs);
+                    has_single_inhabitant_(cx, &field_ty, visited)
+                });
+                visited.remove(&id);
+                single
+            }
+            _ => false,
+        },
+    }
+}
+
+/// Checks only that the match is exhaustive, for callers that don't care about the reachability
+/// of its arms. Returns the patterns it doesn't cover as the error.
+#[cfg(test)]
+pub(crate) fn check_exhaustive<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    arms: &[MatchArm<'p>],
+    scrut_ty: &Ty,
+) -> Result<(), Vec<Pat>> {
+    let report = compute_match_usefulness(cx, arms, scrut_ty);
+    if report.non_exhaustiveness_witnesses.is_empty() {
+        Ok(())
+    } else {
+        Err(report.non_exhaustiveness_witnesses.iter().map(|witness| witness.to_pat(cx)).collect())
+    }
+}
+
+/// Whether the match would be exhaustive if an arm matching any value with the constructor `extra`
+/// was added at the end, like `None => {}` for `extra` the `None` variant. This lets an IDE
+/// preview which arm completes a match.
+#[cfg(test)]
+pub(crate) fn exhaustive_if_added<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    arms: &[MatchArm<'p>],
+    scrut_ty: &Ty,
+    extra: Constructor,
+) -> bool {
+    let scrut_ty = normalize_pat_ty(cx.db, cx.body, scrut_ty);
+    let is_non_exhaustive = cx.is_foreign_non_exhaustive_enum(&scrut_ty);
+    let pcx = PatCtxt { cx, ty: &scrut_ty, is_top_level: true, is_non_exhaustive };
+    let pat = cx.pattern_arena.alloc(DeconstructedPat::wild_from_ctor(pcx, extra));
+    let mut arms = arms.to_vec();
+    arms.push(MatchArm { pat, has_guard: false });
+    compute_match_usefulness(cx, &arms, &scrut_ty).non_exhaustiveness_witnesses.is_empty()
+}
+
+/// Whether the match would still be exhaustive without the arm at `index`. This lets an IDE tell
+/// whether deleting that arm is safe.
+#[cfg(test)]
+pub(crate) fn exhaustive_without<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    arms: &[MatchArm<'p>],
+    scrut_ty: &Ty,
+    index: usize,
+) -> bool {
+    let arms: Vec<_> =
+        arms.iter().enumerate().filter(|&(idx, _)| idx != index).map(|(_, arm)| *arm).collect();
+    compute_match_usefulness(cx, &arms, scrut_ty).non_exhaustiveness_witnesses.is_empty()
+}
+
+/// The values two matches on the same type cover differently, see [`coverage_diff`].
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct CoverageDiff<'p> {
+    /// Witnesses of the values the first match covers but the second doesn't.
+    pub(crate) only_in_a: Vec<DeconstructedPat<'p>>,
+    /// Witnesses of the values the second match covers but the first doesn't.
+    pub(crate) only_in_b: Vec<DeconstructedPat<'p>>,
+}
+
+#[cfg(test)]
+impl CoverageDiff<'_> {
+    /// Whether both matches cover the same values.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.only_in_a.is_empty() && self.only_in_b.is_empty()
+    }
+}
+
+/// Compares the values covered by the arms of two matches on the same type, for instance to check
+/// that splitting or merging matches keeps their coverage. Only arms without a guard count as
+/// covering their values.
+///
+/// Like `compute_match_usefulness`, this marks the patterns of `arms_a` and `arms_b` reachable, so
+/// they shouldn't be checked again afterwards.
+#[cfg(test)]
+pub(crate) fn coverage_diff<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    arms_a: &[MatchArm<'p>],
+    arms_b: &[MatchArm<'p>],
+) -> CoverageDiff<'p> {
+    CoverageDiff {
+        only_in_a: covered_only_by(cx, arms_a, arms_b),
+        only_in_b: covered_only_by(cx, arms_b, arms_a),
+    }
+}
+
+/// Witnesses of the values matched by `arms` but not by `others`: each arm is checked against a
+/// matrix of `others` and the arms before it, and the values it is useful for are collected.
+#[cfg(test)]
+fn covered_only_by<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    arms: &[MatchArm<'p>],
+    others: &[MatchArm<'p>],
+) -> Vec<DeconstructedPat<'p>> {
+    let mut matrix = Matrix::empty();
+    for arm in others.iter().filter(|arm| !arm.has_guard) {
+        matrix.push(PatStack::from_pattern(arm.pat));
+    }
+    let mut witnesses = Vec::new();
+    for arm in arms.iter().filter(|arm| !arm.has_guard) {
+        let v = PatStack::from_pattern(arm.pat);
+        witnesses
+            .extend(is_useful(cx, &matrix, &v, FakeExtraWildcard, false, true).into_witnesses());
+        // The values of the earlier arms were collected already.
+        matrix.push(v);
+    }
+    dedup_witnesses(cx, witnesses)
+}
+
+/// Checks the match and renders the patterns it doesn't cover, like diagnostics show them.
+#[cfg(test)]
+pub(crate) fn non_exhaustive_witness_strings<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    arms: &[MatchArm<'p>],
+    scrut_ty: &Ty,
+) -> Vec<String> {
+    let report = compute_match_usefulness(cx, arms, scrut_ty);
+    render_witnesses(cx, &report.non_exhaustiveness_witnesses, &PatNameCache::default())
+}
+
+/// Renders `witnesses` like [`DisplayWitness`], looking up each variant they refer to once in
+/// `names`, which can be shared by the witnesses of several matches.
+#[cfg(test)]
+pub(crate) fn render_witnesses<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    witnesses: &[DeconstructedPat<'p>],
+    names: &PatNameCache,
+) -> Vec<String> {
+    witnesses.iter().map(|witness| DisplayWitness(witness, cx, names).to_string()).collect()
+}
+
+/// Builds a representation of the witnesses of a match, see
+/// [`UsefulnessReport::feed_witnesses`].
+#[cfg(test)]
+pub(crate) trait WitnessSink<'p> {
+    type Output;
+
+    /// Receives the constructor of `pat`, a witness or one of its fields, before those of its
+    /// fields.
+    fn push_constructor(&mut self, _cx: &MatchCheckCtx<'_, 'p>, _pat: &DeconstructedPat<'p>) {}
+
+    /// Receives each witness, after all of its constructors.
+    fn push_witness(&mut self, _cx: &MatchCheckCtx<'_, 'p>, _witness: &DeconstructedPat<'p>) {}
+
+    fn finish(self) -> Self::Output;
+}
+
+/// The default [`WitnessSink`], which collects the witnesses as patterns.
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct PatWitnessSink {
+    pats: Vec<Pat>,
+}
+
+#[cfg(test)]
+impl<'p> WitnessSink<'p> for PatWitnessSink {
+    type Output = Vec<Pat>;
+
+    fn push_witness(&mut self, cx: &MatchCheckCtx<'_, 'p>, witness: &DeconstructedPat<'p>) {
+        self.pats.push(witness.to_pat(cx));
+    }
+
+    fn finish(self) -> Vec<Pat> {
+        self.pats
+    }
+}
+
+/// Displays a witness as a pattern, like `Some(_)`, with the names it refers to looked up in the
+/// cache.
+pub(crate) struct DisplayWitness<'a, 'p>(
+    pub(crate) &'a DeconstructedPat<'p>,
+    pub(crate) &'a MatchCheckCtx<'a, 'p>,
+    pub(crate) &'a PatNameCache,
+);
+
+impl<'a, 'p> fmt::Display for DisplayWitness<'a, 'p> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let DisplayWitness(witness, cx, names) = *self;
+        let pat = witness.to_pat(cx);
+        write!(f, "{}", pat.display_with_names(names).display(cx.db))
+    }
+}
+
+/// The values of an integer scrutinee that the arms of a match cover and the values they don't,
+/// as sorted lists of inclusive ranges.
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct IntCoverage {
+    pub(crate) covered: Vec<(i128, i128)>,
+    pub(crate) gaps: Vec<(i128, i128)>,
+}
+
+/// Computes which values of the integer type `scrut_ty` are covered by the arms without a guard.
+/// Returns `None` if `scrut_ty` isn't an integer type, or if a bound doesn't fit in an `i128`.
+#[cfg(test)]
+pub(crate) fn int_coverage(arms: &[MatchArm<'_>], scrut_ty: &Ty) -> Option<IntCoverage> {
+    let scalar_ty = match scrut_ty.kind(Interner) {
+        &TyKind::Scalar(scalar_ty @ (Scalar::Int(_) | Scalar::Uint(_))) => scalar_ty,
+        _ => return None,
+    };
+    let mut ranges = Vec::new();
+    for arm in arms.iter().filter(|arm| !arm.has_guard) {
+        arm.pat.collect_int_ranges(scalar_ty, &mut ranges);
+    }
+    let (covered, gaps) = IntRange::coverage(ranges, scalar_ty)?;
+    Some(IntCoverage { covered, gaps })
+}
+
+/// A range of values of an integer scrutinee that more than one arm covers, with the indices of
+/// those arms.
+#[cfg(test)]
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct RangeOverlap {
+    pub(crate) range: (i128, i128),
+    pub(crate) arms: Vec<usize>,
+}
+
+/// Computes the ranges of values of the integer type `scrut_ty` covered by more than one arm
+/// without a guard, split so that each range is covered by the same arms throughout. Unlike
+/// [`UsefulnessReport::range_overlaps`], this includes ranges an arm contains entirely and ranges
+/// covered by several arms at once. Returns `None` if `scrut_ty` isn't an integer type, or if a
+/// bound doesn't fit in an `i128`.
+#[cfg(test)]
+pub(crate) fn range_overlap_map(arms: &[MatchArm<'_>], scrut_ty: &Ty) -> Option<Vec<RangeOverlap>> {
+    let scalar_ty = match scrut_ty.kind(Interner) {
+        &TyKind::Scalar(scalar_ty @ (Scalar::Int(_) | Scalar::Uint(_))) => scalar_ty,
+        _ => return None,
     };
-    UsefulnessReport { _arm_usefulness: arm_usefulness, non_exhaustiveness_witnesses }
+    let mut ranges = Vec::new();
+    for (idx, arm) in arms.iter().enumerate().filter(|(_, arm)| !arm.has_guard) {
+        let mut arm_ranges = Vec::new();
+        arm.pat.collect_int_ranges(scalar_ty, &mut arm_ranges);
+        ranges.extend(arm_ranges.into_iter().map(|range| (idx, range)));
+    }
+    let overlaps = IntRange::overlap_map(&ranges, scalar_ty)?;
+    Some(overlaps.into_iter().map(|(range, arms)| RangeOverlap { range, arms }).collect())
+}
+
+/// Computes the fraction of the values of the integer type `scrut_ty` that the arms without a guard
+/// cover, like `0.5` for `0..=127` on `u8`. Returns `None` if `scrut_ty` isn't an integer type with
+/// a known number of values, which excludes `isize` and `usize`.
+#[cfg(test)]
+pub(crate) fn int_coverage_fraction(arms: &[MatchArm<'_>], scrut_ty: &Ty) -> Option<f64> {
+    if let TyKind::Scalar(Scalar::Int(IntTy::Isize) | Scalar::Uint(UintTy::Usize)) =
+        scrut_ty.kind(Interner)
+    {
+        return None;
+    }
+    let IntCoverage { covered, gaps } = int_coverage(arms, scrut_ty)?;
+    let count = |ranges: &[(i128, i128)]| -> f64 {
+        ranges.iter().map(|&(lo, hi)| hi as f64 - lo as f64 + 1.0).sum()
+    };
+    let covered = count(&covered);
+    Some(covered / (covered + count(&gaps)))
+}
+
+/// An estimate of what an arm adds to the arms above it: the constructors at the top level of the
+/// scrutinee for which the arm matches values that no arm above it matches. It doesn't count the
+/// values themselves: `Some(_)` counts once whether the arm covers one or all of its values.
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct CoverageEstimate {
+    pub(crate) ctors: Vec<Constructor>,
+}
+
+/// Computes, for each arm, the constructors it newly covers relative to the arms without a guard
+/// above it. An unreachable arm covers nothing new.
+#[cfg(test)]
+pub(crate) fn arm_coverage_counts<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    arms: &[MatchArm<'p>],
+) -> Vec<CoverageEstimate> {
+    let mut matrix = Matrix::empty();
+    arms.iter()
+        .map(|arm| {
+            let v = PatStack::from_pattern(arm.pat);
+            let ctors = newly_covered_ctors(cx, &matrix, &v);
+            if !arm.has_guard {
+                matrix.push(v);
+            }
+            CoverageEstimate { ctors }
+        })
+        .collect()
+}
+
+/// The top-level constructors for which `v` is useful relative to `matrix`. Unlike `is_useful`,
+/// a wildcard is split into every constructor of the type instead of the group of the missing
+/// ones, so that the constructors it covers are listed one by one. The patterns are those of the
+/// arms, so this checks them as `FakeExtraWildcard` to leave their reachability alone.
+#[cfg(test)]
+fn newly_covered_ctors<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    matrix: &Matrix<'p>,
+    v: &PatStack<'p>,
+) -> Vec<Constructor> {
+    let mut covered = Vec::new();
+    if v.head().is_or_pat() {
+        // Like in `is_useful`, each alternative only counts what the ones before it don't cover.
+        let mut matrix = matrix.clone();
+        for v in v.expand_or_pat() {
+            for ctor in newly_covered_ctors(cx, &matrix, &v) {
+                if !covered.contains(&ctor) {
+                    covered.push(ctor);
+                }
+            }
+            matrix.push(v);
+        }
+        return covered;
+    }
+
+    let ty = v.head().ty();
+    let is_non_exhaustive = cx.is_foreign_non_exhaustive_enum(ty);
+    let pcx = PatCtxt { cx, ty, is_top_level: true, is_non_exhaustive };
+    let head_ctors = matrix.heads().map(DeconstructedPat::ctor);
+
+    let mut candidates: Vec<Constructor> = Vec::new();
+    let v_ctor = v.head().ctor();
+    if v_ctor.is_wildcard() {
+        let mut split_wildcard = SplitWildcard::new(pcx);
+        split_wildcard.split(pcx, head_ctors);
+        let present = split_wildcard.iter_present(pcx);
+        candidates.extend(present.chain(split_wildcard.iter_missing(pcx)).cloned());
+    } else {
+        candidates.extend(v_ctor.split(pcx, head_ctors));
+    }
+
+    for ctor in candidates {
+        let spec_matrix = matrix.specialize_constructor(pcx, &ctor);
+        let v = v.pop_head_constructor(cx, &ctor);
+        if is_useful(cx, &spec_matrix, &v, FakeExtraWildcard, false, false).is_useful() {
+            covered.push(ctor);
+        }
+    }
+    covered
+}
+
+/// Removes the witnesses that are shown as the same pattern as an earlier witness, keeping the
+/// order of the others.
+pub(super) fn dedup_witnesses<'p>(
+    cx: &MatchCheckCtx<'_, 'p>,
+    witnesses: Vec<DeconstructedPat<'p>>,
+) -> Vec<DeconstructedPat<'p>> {
+    let mut seen = FxHashSet::default();
+    witnesses
+        .into_iter()
+        .filter(|witness| seen.insert(WitnessKey::new(&witness.to_pat(cx))))
+        .collect()
+}
+
+/// What `dedup_witnesses` compares witnesses by: the pattern without its bindings, which don't
+/// change the values it matches. A binding is replaced by its subpattern, or by a wildcard.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub(super) struct WitnessKey(Pat);
+
+impl WitnessKey {
+    pub(super) fn new(pat: &Pat) -> WitnessKey {
+        WitnessKey(pat.fold_with(&mut EraseBindings))
+    }
+}
+
+struct EraseBindings;
+
+impl PatternFolder for EraseBindings {
+    fn fold_pattern(&mut self, pattern: &Pat) -> Pat {
+        match &*pattern.kind {
+            PatKind::Binding { subpattern: Some(subpattern), .. } => subpattern.fold_with(self),
+            PatKind::Binding { subpattern: None, .. } => {
+                Pat { ty: pattern.ty.clone(), kind: Box::new(PatKind::Wild) }
+            }
+            _ => pattern.super_fold_with(self),
+        }
+    }
 }
 
 pub(crate) mod helper {
@@ -291,7 +291,7 @@ pub(super) fn infer_pat(
                 .intern(Interner)
             }
             Pat::Wild => expected.clone(),
-            Pat::Range { start, end } => {
+            Pat::Range { start, end, .. } => {
                 let start_ty = self.infer_expr(*start, &Expectation::has_type(expected.clone()));
                 self.infer_expr(*end, &Expectation::has_type(start_ty))
             }
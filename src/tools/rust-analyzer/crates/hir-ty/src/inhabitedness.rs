@@ -9,6 +9,7 @@
     adt::VariantData, attr::Attrs, type_ref::ConstScalar, visibility::Visibility, AdtId,
     EnumVariantId, HasModule, Lookup, ModuleId, VariantId,
 };
+use rustc_hash::FxHashSet;
 
 use crate::{
     db::HirDatabase, Binders, ConcreteConst, Const, ConstValue, Interner, Substitution, Ty, TyKind,
@@ -16,7 +17,7 @@
 
 /// Checks whether a type is visibly uninhabited from a particular module.
 pub(crate) fn is_ty_uninhabited_from(ty: &Ty, target_mod: ModuleId, db: &dyn HirDatabase) -> bool {
-    let mut uninhabited_from = UninhabitedFrom { target_mod, db };
+    let mut uninhabited_from = UninhabitedFrom { target_mod, db, visiting: FxHashSet::default() };
     let inhabitedness = ty.visit_with(&mut uninhabited_from, DebruijnIndex::INNERMOST);
     inhabitedness == BREAK_VISIBLY_UNINHABITED
 }
@@ -32,7 +33,7 @@ pub(crate) fn is_enum_variant_uninhabited_from(
     let vars_attrs = db.variants_attrs(variant.parent);
     let is_local = variant.parent.lookup(db.upcast()).container.krate() == target_mod.krate();
 
-    let mut uninhabited_from = UninhabitedFrom { target_mod, db };
+    let mut uninhabited_from = UninhabitedFrom { target_mod, db, visiting: FxHashSet::default() };
     let inhabitedness = uninhabited_from.visit_variant(
         variant.into(),
         &enum_data.variants[variant.local_id].variant_data,
@@ -46,6 +47,9 @@ pub(crate) fn is_enum_variant_uninhabited_from(
 struct UninhabitedFrom<'a> {
     target_mod: ModuleId,
     db: &'a dyn HirDatabase,
+    /// The ADTs whose fields are being visited. An ADT that contains itself without indirection
+    /// doesn't compile, but it can still be typed, so it is taken as inhabited, like rustc does.
+    visiting: FxHashSet<AdtId>,
 }
 
 const CONTINUE_OPAQUELY_INHABITED: ControlFlow<VisiblyUninhabited> = Continue(());
@@ -74,6 +78,8 @@ fn visit_ty(
                 Some(1..) => item_ty.super_visit_with(self, outer_binder),
             },
 
+            // A reference is inhabited even if its pointee isn't, so an empty `match` on `&!` is
+            // not exhaustive.
             TyKind::Ref(..) | _ => CONTINUE_OPAQUELY_INHABITED,
         }
     }
@@ -85,6 +91,15 @@ fn interner(&self) -> Interner {
 
 impl UninhabitedFrom<'_> {
     fn visit_adt(&mut self, adt: AdtId, subst: &Substitution) -> ControlFlow<VisiblyUninhabited> {
+        if !self.visiting.insert(adt) {
+            return CONTINUE_OPAQUELY_INHABITED;
+        }
+        let inhabitedness = self.visit_adt_(adt, subst);
+        self.visiting.remove(&adt);
+        inhabitedness
+    }
+
+    fn visit_adt_(&mut self, adt: AdtId, subst: &Substitution) -> ControlFlow<VisiblyUninhabited> {
         let attrs = self.db.attrs(adt.into());
         let adt_non_exhaustive = attrs.by_key("non_exhaustive").exists();
         let is_local = adt.module(self.db.upcast()).krate() == self.target_mod.krate();
@@ -161,7 +176,7 @@ fn visit_field(
     }
 }
 
-fn try_usize_const(c: &Const) -> Option<u128> {
+pub(crate) fn try_usize_const(c: &Const) -> Option<u128> {
     let data = &c.data(Interner);
     if data.ty.kind(Interner) != &TyKind::Scalar(chalk_ir::Scalar::Uint(chalk_ir::UintTy::Usize)) {
         return None;
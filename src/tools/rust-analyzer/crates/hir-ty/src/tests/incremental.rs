@@ -1,8 +1,9 @@
 use std::sync::Arc;
 
-use base_db::{fixture::WithFixture, SourceDatabaseExt};
+use base_db::{fixture::WithFixture, FileId, SourceDatabaseExt};
+use hir_def::expr::Expr;
 
-use crate::{db::HirDatabase, test_db::TestDB};
+use crate::{db::HirDatabase, diagnostics::MatchCheckResult, test_db::TestDB};
 
 use super::visit_module;
 
@@ -49,3 +50,113 @@ fn foo() -> i32 {
         assert!(!format!("{:?}", events).contains("infer"), "{:#?}", events)
     }
 }
+
+fn check_matches(db: &TestDB, file_id: FileId) -> Vec<Option<Arc<MatchCheckResult>>> {
+    let mut results = Vec::new();
+    let module = db.module_for_file(file_id);
+    let crate_def_map = module.def_map(db);
+    visit_module(db, &crate_def_map, module.local_id, &mut |def| {
+        let body = db.body(def);
+        for (id, expr) in body.exprs.iter() {
+            if let Expr::Match { .. } = expr {
+                results.push(db.match_check(def, id));
+            }
+        }
+    });
+    results
+}
+
+#[test]
+fn editing_another_function_should_not_recheck_matches() {
+    let (mut db, pos) = TestDB::with_position(
+        "
+        //- /lib.rs
+        fn foo(x: bool) -> i32 {
+            match x {
+                true => 1,
+            }
+        }
+
+        fn bar() -> i32 {
+            $01 + 1
+        }
+    ",
+    );
+    let before = {
+        let mut results = Vec::new();
+        let events = db.log_executed(|| results = check_matches(&db, pos.file_id));
+        assert!(format!("{:?}", events).contains("match_check"));
+        results
+    };
+    assert_eq!(before.len(), 1);
+    let result = before[0].as_ref().unwrap();
+    assert_eq!(result.arm_reachable, vec![true]);
+    assert_eq!(result.uncovered_patterns.as_deref(), Some("`false` not covered"));
+
+    let new_text = "
+        fn foo(x: bool) -> i32 {
+            match x {
+                true => 1,
+            }
+        }
+
+        fn bar() -> i32 {
+            1
+            +
+            1
+        }
+    "
+    .to_string();
+
+    db.set_file_text(pos.file_id, Arc::new(new_text));
+
+    let after = {
+        let mut results = Vec::new();
+        let events = db.log_executed(|| results = check_matches(&db, pos.file_id));
+        assert!(!format!("{:?}", events).contains("match_check"), "{:#?}", events);
+        results
+    };
+    assert_eq!(before, after);
+}
+
+#[test]
+fn editing_the_same_function_rechecks_its_matches() {
+    let (mut db, pos) = TestDB::with_position(
+        "
+        //- /lib.rs
+        fn foo(x: bool) -> i32 {
+            $0match x {
+                true => 1,
+            }
+        }
+    ",
+    );
+    let before = {
+        let mut results = Vec::new();
+        let events = db.log_executed(|| results = check_matches(&db, pos.file_id));
+        assert!(format!("{:?}", events).contains("match_check"));
+        results
+    };
+
+    // The query reads the whole body, so an edit outside of the match still
+    // checks it again, even though the result doesn't change.
+    let new_text = "
+        fn foo(x: bool) -> i32 {
+            let _y = 2;
+            match x {
+                true => 1,
+            }
+        }
+    "
+    .to_string();
+
+    db.set_file_text(pos.file_id, Arc::new(new_text));
+
+    let after = {
+        let mut results = Vec::new();
+        let events = db.log_executed(|| results = check_matches(&db, pos.file_id));
+        assert!(format!("{:?}", events).contains("match_check"), "{:#?}", events);
+        results
+    };
+    assert_eq!(before, after);
+}
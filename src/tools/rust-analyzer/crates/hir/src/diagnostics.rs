@@ -34,6 +34,7 @@ fn from(d: $diag) -> AnyDiagnostic {
     InactiveCode,
     IncorrectCase,
     InvalidDeriveTarget,
+    IrrefutableLetPatterns,
     MacroError,
     MalformedDerive,
     MismatchedArgCount,
@@ -41,6 +42,7 @@ fn from(d: $diag) -> AnyDiagnostic {
     MissingMatchArms,
     MissingUnsafe,
     NoSuchField,
+    RefutablePattern,
     ReplaceFilterMapNextWithFindMap,
     TypeMismatch,
     UnimplementedBuiltinMacro,
@@ -161,6 +163,20 @@ pub struct MissingMatchArms {
     pub uncovered_patterns: String,
 }
 
+#[derive(Debug)]
+pub struct IrrefutableLetPatterns {
+    /// The condition of an `if let` chain, or the pattern of a `let ... else`.
+    pub node: InFile<SyntaxNodePtr>,
+    pub let_else: bool,
+}
+
+#[derive(Debug)]
+pub struct RefutablePattern {
+    pub pat: InFile<AstPtr<ast::Pat>>,
+    pub context: PatternContext,
+    pub uncovered_patterns: String,
+}
+
 #[derive(Debug)]
 pub struct TypeMismatch {
     // FIXME: add mismatches in patterns as well
@@ -169,4 +185,4 @@ pub struct TypeMismatch {
     pub actual: Type,
 }
 
-pub use hir_ty::diagnostics::IncorrectCase;
+pub use hir_ty::diagnostics::{IncorrectCase, PatternContext};
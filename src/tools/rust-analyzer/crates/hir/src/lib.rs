@@ -82,10 +82,11 @@
     attrs::{HasAttrs, Namespace},
     diagnostics::{
         AnyDiagnostic, BreakOutsideOfLoop, InactiveCode, IncorrectCase, InvalidDeriveTarget,
-        MacroError, MalformedDerive, MismatchedArgCount, MissingFields, MissingMatchArms,
-        MissingUnsafe, NoSuchField, ReplaceFilterMapNextWithFindMap, TypeMismatch,
-        UnimplementedBuiltinMacro, UnresolvedExternCrate, UnresolvedImport, UnresolvedMacroCall,
-        UnresolvedModule, UnresolvedProcMacro,
+        IrrefutableLetPatterns, MacroError, MalformedDerive, MismatchedArgCount, MissingFields,
+        MissingMatchArms, MissingUnsafe, NoSuchField, RefutablePattern,
+        ReplaceFilterMapNextWithFindMap, TypeMismatch, UnimplementedBuiltinMacro,
+        UnresolvedExternCrate, UnresolvedImport, UnresolvedMacroCall, UnresolvedModule,
+        UnresolvedProcMacro,
     },
     has_source::HasSource,
     semantics::{PathResolution, Semantics, SemanticsScope, TypeInfo, VisibleTraits},
@@ -1432,6 +1433,35 @@ pub fn diagnostics(self, db: &dyn HirDatabase, acc: &mut Vec<AnyDiagnostic>) {
                         Err(SyntheticSyntax) => (),
                     }
                 }
+                BodyValidationDiagnostic::IrrefutableLetPatterns { target } => {
+                    let node: Result<InFile<SyntaxNodePtr>, _> = match target {
+                        Either::Left(condition) => {
+                            source_map.expr_syntax(condition).map(|it| it.map(Into::into))
+                        }
+                        Either::Right(pat) => source_map
+                            .pat_syntax(pat)
+                            .map(|it| it.map(|it| it.either(Into::into, Into::into))),
+                    };
+                    if let Ok(node) = node {
+                        acc.push(
+                            IrrefutableLetPatterns { node, let_else: target.is_right() }.into(),
+                        );
+                    }
+                }
+                BodyValidationDiagnostic::RefutablePattern { pat, context, uncovered_patterns } => {
+                    if let Ok(source_ptr) = source_map.pat_syntax(pat) {
+                        if let Some(pat) = source_ptr.value.left() {
+                            acc.push(
+                                RefutablePattern {
+                                    pat: InFile::new(source_ptr.file_id, pat),
+                                    context,
+                                    uncovered_patterns,
+                                }
+                                .into(),
+                            );
+                        }
+                    }
+                }
             }
         }
 
@@ -0,0 +1,66 @@
+use crate::{Diagnostic, DiagnosticsContext, Severity};
+
+// Diagnostic: irrefutable-let-patterns
+//
+// This diagnostic is triggered if the patterns of an `if let` chain or of a `let ... else` match
+// every value, so that the `else` branch is unreachable.
+pub(crate) fn irrefutable_let_patterns(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::IrrefutableLetPatterns,
+) -> Diagnostic {
+    let construct = if d.let_else { "let...else" } else { "if let" };
+    Diagnostic::new(
+        "irrefutable-let-patterns",
+        format!("irrefutable `{construct}` pattern"),
+        ctx.sema.diagnostics_display_range(d.node.clone()).range,
+    )
+    .severity(Severity::WeakWarning)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn if_let() {
+        check_diagnostics(
+            r#"
+//- minicore: option
+fn main(x: Option<i32>, y: (i32, bool)) {
+    if let (a, b) = y {}
+     //^^^^^^^^^^^^^^ weak: irrefutable `if let` pattern
+    if let Some(a) = x {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn let_chains() {
+        check_diagnostics(
+            r#"
+//- minicore: option
+fn main(x: Option<i32>, y: (i32, bool)) {
+    if let (a, _) = y && let b = a {}
+     //^^^^^^^^^^^^^^^^^^^^^^^^^^^ weak: irrefutable `if let` pattern
+    if let (a, _) = y && let Some(b) = x {}
+    if let (a, _) = y && a > 0 {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn let_else() {
+        check_diagnostics(
+            r#"
+//- minicore: option
+fn main(x: Option<i32>, y: (i32, bool)) {
+    let (a, b) = y else { return };
+      //^^^^^^ weak: irrefutable `let...else` pattern
+    let Some(c) = x else { return };
+}
+"#,
+        );
+    }
+}
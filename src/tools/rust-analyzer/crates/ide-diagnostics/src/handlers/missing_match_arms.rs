@@ -800,6 +800,104 @@ fn main() {
         );
     }
 
+    #[test]
+    fn single_variant_enum() {
+        check_diagnostics_no_bails(
+            r#"
+//- /lib.rs crate:lib
+#[non_exhaustive]
+pub enum Foreign { Only(i32) }
+
+//- /main.rs crate:main deps:lib
+use lib::Foreign;
+enum Single { Only(i32) }
+fn main(s: Single, f: Foreign) {
+    match s {
+        Single::Only(_) => {}
+    }
+    match f {
+        //^ error: missing match arm: `_` not covered
+        Foreign::Only(_) => {}
+    }
+    match f {
+        Foreign::Only(_) => {}
+        _ => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn enum_non_exhaustive_reexported() {
+        // Whether the enum is foreign depends on the crate defining it, not on the path it is
+        // named through.
+        check_diagnostics_no_bails(
+            r#"
+//- /lib.rs crate:lib
+#[non_exhaustive]
+pub enum E { A, B }
+
+//- /reexport.rs crate:reexport deps:lib
+pub use lib::E;
+fn _reexporting(e: E) {
+    match e {
+        //^ error: missing match arm: `_` not covered
+        E::A | E::B => {}
+    }
+}
+
+//- /main.rs crate:main deps:reexport
+use reexport::E;
+fn main(e: E) {
+    match e {
+        //^ error: missing match arm: `_` not covered
+        E::A | E::B => {}
+    }
+    match e {
+        E::A | E::B => {}
+        _ => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn collections_without_deref_patterns() {
+        // Without deref patterns, collections with private fields can only be matched by a
+        // wildcard or a binding.
+        check_diagnostics_no_bails(
+            r#"
+//- /main.rs crate:main deps:alloc
+use alloc::{string::String, vec::Vec};
+fn main(v: Vec<i32>, s: String) {
+    match v {}
+        //^ error: missing match arm: type `Vec<i32>` is non-empty
+    match v {
+        _ => {}
+    }
+    match v {
+        w => {}
+    }
+    match s {}
+        //^ error: missing match arm: type `String` is non-empty
+    match s {
+        t => {}
+    }
+}
+
+//- /lib.rs crate:alloc
+pub mod vec {
+    pub struct Vec<T> { ptr: *const T, len: usize }
+}
+pub mod string {
+    pub struct String { vec: crate::vec::Vec<u8> }
+}
+"#,
+        );
+    }
+
     #[test]
     fn match_guard() {
         check_diagnostics_no_bails(
@@ -820,6 +918,24 @@ fn main() {
         );
     }
 
+    #[test]
+    fn match_guard_on_binding() {
+        check_diagnostics_no_bails(
+            r#"
+fn main(x: i32, y: Option<i32>) {
+    match x {
+        n if n > 5 => {}
+        _ => {}
+    }
+    match y {
+        Some(n) if n > 5 => {}
+        n => {}
+    }
+}
+"#,
+        );
+    }
+
     #[test]
     fn pattern_type_is_of_substitution() {
         check_diagnostics_no_bails(
@@ -947,6 +1063,669 @@ fn f() {
         );
     }
 
+    #[test]
+    fn char_ranges_with_const_bounds() {
+        check_diagnostics_no_bails(
+            r#"
+const PRED: char = '\u{D7FF}';
+const SUCC: char = '\u{E000}';
+fn main(c: char) {
+    match c {
+        '\0'..=PRED => {}
+        SUCC..='\u{10FFFF}' => {}
+    }
+    match c {
+        //^ error: missing match arm: `'\u{e000}'..='\u{10ffff}'` not covered
+        '\0'..=PRED => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unevaluated_char_const_is_opaque() {
+        check_diagnostics_no_bails(
+            r#"
+fn foo() -> char { 'a' }
+const A: char = foo();
+fn main(c: char) {
+    match c {
+        A => {}
+        _ => {}
+    }
+    match c {
+        //^ error: missing match arm: `'\0'..='\u{d7ff}'` and `'\u{e000}'..='\u{10ffff}'` not covered
+        A => {}
+        A..='z' => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn generic_scrutinee() {
+        check_diagnostics_no_bails(
+            r#"
+fn main<T>(t: T) {
+    match t {
+        x => {}
+    }
+    match t {}
+        //^ error: missing match arm: type `T` is non-empty
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn wildcard_fields_are_cached() {
+        cov_mark::check!(match_check_wildcards_cached);
+        check_diagnostics_no_bails(
+            r#"
+fn main(p: ((bool, bool), bool)) {
+    match p {
+        ((true, _), _) => {}
+        (_, true) => {}
+        (_, false) => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn integer_ranges() {
+        check_diagnostics_no_bails(
+            r#"
+fn main(x: u8, y: i8) {
+    match x {
+        0 => {}
+        1..=255 => {}
+    }
+    match x {
+        //^ error: missing match arm: `255` not covered
+        0 => {}
+        1..=254 => {}
+    }
+    match y {
+        -128..=-1 => {}
+        0..=127 => {}
+    }
+    match y {
+        //^ error: missing match arm: `-128..=-2` and `0` not covered
+        -1 => {}
+        1..=127 => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn exclusive_integer_ranges() {
+        check_diagnostics_no_bails(
+            r#"
+fn main(x: u8) {
+    match x {
+        0..128 => {}
+        128..=255 => {}
+    }
+    match x {
+        //^ error: missing match arm: `255` not covered
+        0..255 => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn smart_pointer_with_unnormalized_field() {
+        check_diagnostics_no_bails(
+            r#"
+trait ToOwned { type Owned; }
+enum Cow<'a, B: ?Sized + ToOwned + 'a> {
+    Borrowed(&'a B),
+    Owned(<B as ToOwned>::Owned),
+}
+fn main(c: Cow<'_, str>, d: Cow<'_, str>) {
+    match c {
+        _ => {}
+    }
+    match d {}
+        //^ error: missing match arm: `Borrowed(_)` and `Owned(_)` not covered
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn tuple_rest_in_the_middle() {
+        check_diagnostics_no_bails(
+            r#"
+fn main(t: (bool, bool, bool)) {
+    match t {
+        (true, ..) => {}
+        (.., true) => {}
+        (false, .., false) => {}
+    }
+    match t {
+        //^ error: missing match arm: `(false, _, false)` not covered
+        (true, ..) => {}
+        (.., true) => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn recursive_types() {
+        check_diagnostics_no_bails(
+            r#"
+#[lang = "owned_box"]
+pub struct Box<T: ?Sized>(*mut T);
+
+enum List { Nil, Cons(Box<List>) }
+enum Tree<'a> { Leaf, Node(&'a Tree<'a>, &'a Tree<'a>) }
+
+fn main(l: List, t: Tree<'_>) {
+    match l {
+        //^ error: missing match arm: `Cons(_)` not covered
+        List::Nil => {}
+    }
+    match t {
+        //^ error: missing match arm: `Node(_, _)` not covered
+        Tree::Leaf => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn byte_string_patterns() {
+        check_diagnostics_no_bails(
+            r#"
+fn main(b: &[u8; 2], s: &[u8]) {
+    match b {
+        b"ab" => {}
+        _ => {}
+    }
+    match s {
+        b"ab" => {}
+        b"" => {}
+        _ => {}
+    }
+    match b {
+        //^ error: missing match arm: `&_` not covered
+        b"ab" => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn str_patterns() {
+        check_diagnostics_no_bails(
+            r#"
+fn main(s: &str, t: &&str) {
+    match s {
+        "a" => {}
+        "b" => {}
+        _ => {}
+    }
+    match t {
+        "a" => {}
+        _ => {}
+    }
+    match s {
+        //^ error: missing match arm: `&_` not covered
+        "a" => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn adt_const_patterns() {
+        check_diagnostics_no_bails(
+            r#"
+//- minicore: option
+const NONE: Option<u8> = None;
+const SOME_ZERO: Option<u8> = Some(0);
+const fn none() -> Option<u8> { None }
+const CALLED: Option<u8> = none();
+
+fn main(x: Option<u8>) {
+    match x {
+        NONE => {}
+        Some(_) => {}
+    }
+    match x {
+        NONE => {}
+        SOME_ZERO => {}
+        Some(1..=255) => {}
+    }
+    match x {
+        //^ error: missing match arm: `Some(1..=255)` not covered
+        NONE => {}
+        SOME_ZERO => {}
+    }
+    match x {
+        //^ error: missing match arm: `None` not covered
+        CALLED => {}
+        Some(_) => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn reference_patterns() {
+        check_diagnostics_no_bails(
+            r#"
+fn main() {
+    match &false {
+        //^^^^^^ error: missing match arm: `&false` not covered
+        &true => {}
+    }
+    match (&false,) {
+        //^^^^^^^^^ error: missing match arm: `(&false,)` not covered
+        (&true,) => {}
+    }
+    match (&false,) {
+        (&true,) => {}
+        (&false,) => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn slice_patterns() {
+        check_diagnostics_no_bails(
+            r#"
+fn main(s: &[u8], a: [u8; 3]) {
+    match s {
+        &[] => {}
+        &[_, ..] => {}
+    }
+    match s {
+        [] => {}
+        [first, .., last] => {}
+    }
+    match s {
+        //^ error: missing match arm: `&[]` and `&[_, _, ..]` not covered
+        &[_] => {}
+    }
+    match a {
+        //^ error: missing match arm: `[1..=255, _, 1..=255]` not covered
+        [0, ..] => {}
+        [.., 0] => {}
+    }
+    match a {
+        [0, ..] => {}
+        [.., 0] => {}
+        [_, _, _] => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn associated_type_scrutinee() {
+        check_diagnostics_no_bails(
+            r#"
+//- minicore: option
+trait Trait { type Output; }
+fn resolved<T: Trait<Output = Option<i32>>>(x: T::Output) {
+    match x {
+        //^ error: missing match arm: `None` not covered
+        Some(_) => {}
+    }
+    match x {
+        Some(_) => {}
+        None => {}
+    }
+}
+fn unresolved<T: Trait>(x: T::Output) {
+    match x {
+        _ => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn bool_triples() {
+        check_diagnostics_no_bails(
+            r#"
+fn main(x: (bool, bool, bool)) {
+    match x {
+        //^ error: missing match arm: `(false, true, _)`, `(true, false, false)` and `(true, true, false)` not covered
+        (true, true, true) => {}
+        (false, false, _) => {}
+        (true, false, true) => {}
+    }
+    match x {
+        //^ error: missing match arm: `(false, false, false)` not covered
+        (_, _, true) => {}
+        (true, _, false) => {}
+        (false, true, false) => {}
+    }
+    match x {
+        (true, true, true) => {}
+        (true, true, false) => {}
+        (true, false, true) => {}
+        (true, false, false) => {}
+        (false, true, true) => {}
+        (false, true, false) => {}
+        (false, false, true) => {}
+        (false, false, false) => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn array_patterns_behind_references() {
+        check_diagnostics_no_bails(
+            r#"
+fn main(a: &[bool; 2]) {
+    match a {
+        //^ error: missing match arm: `&[false, false]` not covered
+        &[true, ..] => {}
+        &[.., true] => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn empty_array_and_slice_patterns() {
+        check_diagnostics_no_bails(
+            r#"
+fn main(a: [u8; 0], s: &[u8]) {
+    match a {
+        [] => {}
+    }
+    match s {
+        //^ error: missing match arm: `&[_, ..]` not covered
+        &[] => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn record_witnesses_elide_wildcard_fields() {
+        check_diagnostics_no_bails(
+            r#"
+//- minicore: option
+struct S { a: Option<i32>, b: bool, c: u8 }
+enum E { V { a: Option<i32>, b: bool }, W }
+fn main(s: S, e: E) {
+    match e {
+        //^ error: missing match arm: `V { .. }` not covered
+        E::W => {}
+    }
+    match s {
+        //^ error: missing match arm: `S { a: Some(_), .. }` not covered
+        S { a: None, .. } => {}
+    }
+    match s {
+        //^ error: missing match arm: `S { c: 255, .. }` not covered
+        S { c: 0..=254, .. } => {}
+    }
+    match s {
+        //^ error: missing match arm: `S { a: Some(_), b: true, .. }` not covered
+        S { a: None, .. } => {}
+        S { b: false, .. } => {}
+    }
+    match s {
+        //^ error: missing match arm: `S { a: None, b: false, c: 0 }` not covered
+        S { a: Some(_), .. } => {}
+        S { b: true, .. } => {}
+        S { c: 1..=255, .. } => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extreme_u128_endpoints() {
+        check_diagnostics_no_bails(
+            r#"
+fn main(x: u128) {
+    match x {
+        0..=340282366920938463463374607431768211455 => {}
+    }
+    match x {
+        0..=10 => {}
+        11..=340282366920938463463374607431768211455 => {}
+    }
+    match x {
+        340282366920938463463374607431768211455 => {}
+        0..340282366920938463463374607431768211455 => {}
+    }
+    match x {
+        //^ error: missing match arm: `340282366920938463463374607431768211455` not covered
+        0..340282366920938463463374607431768211455 => {}
+    }
+    match x {
+        //^ error: missing match arm: `0` not covered
+        1..=340282366920938463463374607431768211455 => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn signed_witnesses() {
+        check_diagnostics_no_bails(
+            r#"
+fn main(x: i8, y: i128) {
+    match x {
+        //^ error: missing match arm: `-128..=-1` not covered
+        0..=127 => {}
+    }
+    match x {
+        //^ error: missing match arm: `-5` not covered
+        -128..=-6 => {}
+        -4..=127 => {}
+    }
+    match x {
+        //^ error: missing match arm: `0..=127` not covered
+        -128..=-1 => {}
+    }
+    match y {
+        //^ error: missing match arm: `-170141183460469231731687303715884105728..=-1` not covered
+        0..=170141183460469231731687303715884105727 => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn fieldless_std_enums() {
+        check_diagnostics_no_bails(
+            r#"
+//- minicore: ord, try
+use core::cmp::Ordering;
+use core::ops::ControlFlow;
+fn main(x: Ordering, y: ControlFlow<u8, bool>) {
+    match x {
+        Ordering::Less => {}
+        Ordering::Equal => {}
+        Ordering::Greater => {}
+    }
+    match x {
+        //^ error: missing match arm: `Greater` not covered
+        Ordering::Less | Ordering::Equal => {}
+    }
+    match y {
+        //^ error: missing match arm: `Continue(false)` not covered
+        ControlFlow::Continue(true) => {}
+        ControlFlow::Break(_) => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn default_binding_modes() {
+        check_diagnostics_no_bails(
+            r#"
+//- minicore: option
+fn main(x: &Option<i32>) {
+    match x {
+        Some(_) => {}
+        None => {}
+    }
+    match x {
+        &Some(_) => {}
+        &None => {}
+    }
+    match x {
+        //^ error: missing match arm: `&None` not covered
+        Some(_) => {}
+    }
+    match x {
+        //^ error: missing match arm: `&None` not covered
+        &Some(_) => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn bindings_with_range_subpatterns() {
+        check_diagnostics_no_bails(
+            r#"
+fn main(x: u8) {
+    match x {
+        n @ 0..=127 => {}
+        n @ 128..=255 => {}
+    }
+    match x {
+        //^ error: missing match arm: `128..=255` not covered
+        n @ 0..=127 => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn tuple_variant_with_literal_and_binding() {
+        check_diagnostics_no_bails(
+            r#"
+enum MyEnum { Variant(u8, bool) }
+fn main(x: MyEnum) {
+    match x {
+        MyEnum::Variant(0, y) => {}
+        MyEnum::Variant(_, _) => {}
+    }
+    match x {
+        //^ error: missing match arm: `Variant(1..=255, _)` not covered
+        MyEnum::Variant(0, y) => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn guarded_arm_does_not_cover_its_pattern() {
+        check_diagnostics_no_bails(
+            r#"
+//- minicore: option
+fn main(x: Option<i32>, c: bool) {
+    match x {
+        //^ error: missing match arm: `Some(_)` not covered
+        None => {}
+        Some(_) if c => {}
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn match_position_does_not_matter() {
+        check_diagnostics_no_bails(
+            r#"
+//- minicore: option
+fn statement(x: Option<bool>) {
+    match x {
+        //^ error: missing match arm: `None` not covered
+        Some(true) => {}
+        Some(_) => {}
+        Some(false) => {}
+    }
+}
+fn initializer(x: Option<bool>) -> i32 {
+    let y = match x {
+                //^ error: missing match arm: `None` not covered
+        Some(true) => 1,
+        Some(_) => 2,
+        Some(false) => 3,
+    };
+    y
+}
+fn tail(x: Option<bool>) -> i32 {
+    match x {
+        //^ error: missing match arm: `None` not covered
+        Some(true) => 1,
+        Some(_) => 2,
+        Some(false) => 3,
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn repr_enum_matched_by_variant() {
+        check_diagnostics_no_bails(
+            r#"
+#[repr(u8)]
+enum E { A, B, C }
+#[repr(u8)]
+enum F { A = 1, B = 2, C = 4 }
+fn main(x: E, y: F) {
+    match x {
+        //^ error: missing match arm: `C` not covered
+        E::A => {}
+        E::B => {}
+    }
+    match y {
+        F::A => {}
+        F::B => {}
+        F::C => {}
+    }
+}
+"#,
+        );
+    }
+
     mod rust_unstable {
         use super::*;
 
@@ -969,6 +1748,51 @@ fn test() {
             );
         }
 
+        #[test]
+        fn rfc_1872_infallible() {
+            check_diagnostics_no_bails(
+                r"
+//- minicore: result, infallible
+//- /lib.rs crate:lib
+use core::convert::Infallible;
+fn test(x: Result<u8, Infallible>) {
+    match x { Ok(_) => () }
+    //    ^ error: missing match arm: `Err(_)` not covered
+}
+//- /main.rs crate:main deps:lib
+#![feature(exhaustive_patterns)]
+use core::convert::Infallible;
+fn test(x: Result<u8, Infallible>) {
+    match x { Ok(_) => () }
+}",
+            );
+        }
+
+        #[test]
+        fn rfc_1872_uninhabited_struct_fields() {
+            check_diagnostics_no_bails(
+                r"
+//- minicore: option
+//- /lib.rs crate:lib
+enum Never {}
+struct S { x: bool, n: Never }
+fn test(s: S, o: Option<S>) {
+    match s {}
+    //    ^ error: missing match arm: type `S` is non-empty
+    match o { None => {} }
+    //    ^ error: missing match arm: `Some(_)` not covered
+}
+//- /main.rs crate:main deps:lib
+#![feature(exhaustive_patterns)]
+enum Never {}
+struct S { x: bool, n: Never }
+fn test(s: S, o: Option<S>) {
+    match s {}
+    match o { None => {} }
+}",
+            );
+        }
+
         #[test]
         fn rfc_1872_private_uninhabitedness() {
             check_diagnostics_no_bails(
@@ -1007,13 +1831,13 @@ mod false_negatives {
         fn integers() {
             cov_mark::check_count!(validate_match_bailed_out, 1);
 
-            // We don't currently check integer exhaustiveness.
+            // We don't currently check half-open range patterns.
             check_diagnostics(
                 r#"
 fn main() {
     match 5 {
         10 => (),
-        11..20 => (),
+        11.. => (),
     }
 }
 "#,
@@ -1021,14 +1845,14 @@ fn main() {
         }
 
         #[test]
-        fn reference_patterns_at_top_level() {
+        fn reference_patterns_in_fields() {
             cov_mark::check_count!(validate_match_bailed_out, 1);
 
             check_diagnostics(
                 r#"
 fn main() {
-    match &false {
-        &true => {}
+    match (&false,) {
+        (true,) => {}
     }
 }
             "#,
@@ -1036,20 +1860,29 @@ fn main() {
         }
 
         #[test]
-        fn reference_patterns_in_fields() {
-            cov_mark::check_count!(validate_match_bailed_out, 2);
+        fn deref_patterns_on_user_types() {
+            cov_mark::check_count!(validate_match_bailed_out, 1);
 
+            // There are no deref patterns yet, and `box` patterns only apply to `Box`, so we don't
+            // look through user types implementing `Deref`.
             check_diagnostics(
                 r#"
-fn main() {
-    match (&false,) {
-        (true,) => {}
-    }
-    match (&false,) {
-        (&true,) => {}
+//- minicore: deref
+#[lang = "owned_box"]
+pub struct Box<T: ?Sized>(*mut T);
+
+struct MyBox<T>(T);
+impl<T> core::ops::Deref for MyBox<T> {
+    type Target = T;
+    fn deref(&self) -> &T { &self.0 }
+}
+
+fn main(b: MyBox<bool>) {
+    match b {
+        box true => {}
     }
 }
-            "#,
+"#,
             );
         }
     }
@@ -0,0 +1,86 @@
+use crate::{Diagnostic, DiagnosticsContext};
+
+// Diagnostic: refutable-pattern
+//
+// This diagnostic is triggered if a pattern that has to match every value of its type, like the
+// pattern of a `let` statement or of a function parameter, doesn't.
+pub(crate) fn refutable_pattern(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::RefutablePattern,
+) -> Diagnostic {
+    Diagnostic::new(
+        "refutable-pattern",
+        format!("{}: {}", d.context.refutable_message(), d.uncovered_patterns),
+        ctx.sema.diagnostics_display_range(d.pat.clone().map(|it| it.into())).range,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn let_binding() {
+        check_diagnostics(
+            r#"
+//- minicore: option
+fn main(x: Option<i32>) {
+    let Some(y) = x;
+      //^^^^^^^ error: refutable pattern in local binding: `None` not covered
+    let (a, b) = (1, x);
+    let Some(z) = x else { return };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn fn_and_closure_params() {
+        check_diagnostics(
+            r#"
+//- minicore: option
+fn f((a, 0): (i32, u8)) {}
+   //^^^^^^ error: refutable pattern in function parameter: `(_, 1..=255)` not covered
+fn main() {
+    let g = |Some(x): Option<bool>| x;
+           //^^^^^^^ error: refutable pattern in function parameter: `None` not covered
+    let h = |(a, b): (bool, bool)| a && b;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn for_loop() {
+        check_diagnostics(
+            r#"
+//- minicore: iterator, option
+struct It;
+impl Iterator for It {
+    type Item = Option<(i32, i32)>;
+    fn next(&mut self) -> Option<Self::Item> { None }
+}
+fn main() {
+    for Some(x) in It {}
+      //^^^^^^^ error: refutable pattern in `for` loop binding: `None` not covered
+    for Some((a, b)) | None in It {}
+    for x in It {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn uninhabited_variants_with_exhaustive_patterns() {
+        check_diagnostics(
+            r#"
+#![feature(exhaustive_patterns)]
+enum Void {}
+enum Either { Left(i32), Right(Void) }
+fn main(x: Either) {
+    let Either::Left(y) = x;
+}
+"#,
+        );
+    }
+}
@@ -27,6 +27,7 @@
 //!     generator: pin
 //!     hash:
 //!     index: sized
+//!     infallible:
 //!     iterator: option
 //!     iterators: iterator, fn
 //!     option:
@@ -150,6 +151,10 @@ pub trait AsRef<T: ?Sized> {
         fn as_ref(&self) -> &T;
     }
     // endregion:as_ref
+
+    // region:infallible
+    pub enum Infallible {}
+    // endregion:infallible
 }
 
 pub mod ops {